@@ -0,0 +1,74 @@
+use candid::Principal;
+use ic_cdk::api::{caller, is_controller};
+use ic_cdk_macros::{init, post_upgrade, query, update};
+
+use crate::models::config::{KycArgs, KycConfig, DEFAULT_MERCX_BACKEND};
+use crate::store::CONFIG;
+use crate::user_queries::is_admin;
+
+fn set_config(config: KycConfig) {
+    CONFIG.with(|c| {
+        let _ = c.borrow_mut().set(config);
+    });
+}
+
+fn apply_args(args: Option<KycArgs>) {
+    if let Some(mercx_backend) = args.and_then(|args| args.mercx_backend) {
+        set_config(KycConfig { mercx_backend: Some(mercx_backend) });
+    }
+}
+
+#[init]
+fn init(args: Option<KycArgs>) {
+    apply_args(args);
+}
+
+#[post_upgrade]
+fn post_upgrade(args: Option<KycArgs>) {
+    apply_args(args);
+}
+
+/// mercx_backend canister that verify_kyc whitelists users on
+pub fn mercx_backend() -> Principal {
+    CONFIG
+        .with(|c| c.borrow().get().mercx_backend)
+        .unwrap_or_else(|| Principal::from_text(DEFAULT_MERCX_BACKEND).unwrap())
+}
+
+#[query]
+pub fn get_mercx_backend() -> Principal {
+    mercx_backend()
+}
+
+#[update]
+pub fn set_mercx_backend(principal: Principal) -> Result<Principal, String> {
+    if !is_controller(&caller()) {
+        is_admin()?;
+    }
+    set_config(KycConfig { mercx_backend: Some(principal) });
+    Ok(principal)
+}
+
+/// Keeps the KYC cache in mercx_backend in sync, failures are only logged
+/// since mercx_backend refreshes stale entries on its own
+pub fn push_user(principal: Principal, user_id: u32, kyc_status: bool) {
+    ic_cdk::spawn(async move {
+        if let Err(err) = ic_cdk::call::<(Principal, u32, bool), ()>(
+            mercx_backend(),
+            "push_kyc_user",
+            (principal, user_id, kyc_status),
+        )
+        .await
+        {
+            ic_cdk::println!("❌ Failed to push KYC user {} to mercx_backend: {:?}", principal, err);
+        }
+    });
+}
+
+pub fn remove_user(principal: Principal) {
+    ic_cdk::spawn(async move {
+        if let Err(err) = ic_cdk::call::<(Principal,), ()>(mercx_backend(), "remove_kyc_user", (principal,)).await {
+            ic_cdk::println!("❌ Failed to remove KYC user {} from mercx_backend: {:?}", principal, err);
+        }
+    });
+}
@@ -1 +1,2 @@
-pub mod user;
\ No newline at end of file
+pub mod user;
+pub mod config;
\ No newline at end of file
@@ -10,6 +10,7 @@ use crate::models::user::{SignupRequest, UpdateUserRequest, User};
 use crate::validations::user::{validate_username, validate_name, validate_avatar_url};
 use crate::user_queries::get_user;
 use crate::user_queries::is_admin;
+use crate::api::config::{mercx_backend, push_user, remove_user};
 use std::sync::atomic::{AtomicU32, Ordering};
 
 static USER_ID_COUNTER: AtomicU32 = AtomicU32::new(1); // Start at 1
@@ -90,6 +91,8 @@ pub fn signup(request: SignupRequest) -> Result<User, String> {
         Ok::<(), String>(())
     })?;
 
+    push_user(caller, user_id, false);
+
     Ok(user)
 }
 
@@ -158,22 +161,25 @@ pub fn upgrade_to_librarian() -> Result<User, String> {
 pub async fn verify_kyc(principal: Principal) -> Result<String, String> {
     is_admin()?; // Ensure only admins can perform this action
 
-    USERS.with(|users| {
+    let user_id = USERS.with(|users| {
         let mut users = users.borrow_mut();
         if let Some(mut user) = users.get(&principal) {
             user.kyc_status = true;
+            let user_id = user.user_id;
 
             // Re-insert the updated user into the map
             users.insert(principal, user);
 
-            Ok("KYC verification successful.".to_string())
+            Ok(user_id)
         } else {
             return Err("User not found.".to_string());
         }
     })?; 
 
+    push_user(principal, user_id, true);
+
     // Call `add_to_whitelist` in `mercx_backend`
-    let mercx_backend_canister_id: Principal = Principal::from_text("zoa6c-riaaa-aaaan-qzmta-cai").unwrap();
+    let mercx_backend_canister_id: Principal = mercx_backend();
 
     match call::<(Principal,), ()>(mercx_backend_canister_id, "add_to_whitelist", (principal,)).await {
         Ok(_) => Ok("KYC verified and user added to whitelist.".to_string()),
@@ -221,6 +227,8 @@ pub fn delete_user(principal: Principal) -> Result<String, String> {
                 }
             });
 
+            remove_user(principal);
+
             Ok(format!("User '{}' deleted successfully.", user.username))
         } else {
             Err("User not found.".to_string())
@@ -250,6 +258,12 @@ pub fn delete_all_users() -> Result<String, String> {
     //     return Err("Only admin can delete all users.".to_string());
     // }
 
+    // the backend keeps its own copy of each user, drop those first
+    let principals: Vec<Principal> = USERS.with(|users| users.borrow().iter().map(|(principal, _)| principal).collect());
+    for principal in principals {
+        remove_user(principal);
+    }
+
     USERS.with(|users| {
         users.borrow_mut().clear_new(); // ✅ clears all users
     });
@@ -7,6 +7,7 @@ pub mod api;
 use candid::Principal;
 
 pub use models::user::*;
+pub use models::config::KycArgs;
 
 pub use validations::user::*;
 
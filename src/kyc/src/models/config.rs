@@ -0,0 +1,20 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+// mercx_backend canister used when no id was configured
+#[cfg(not(feature = "prod"))]
+pub const DEFAULT_MERCX_BACKEND: &str = "ahw5u-keaaa-aaaaa-qaaha-cai";
+
+#[cfg(feature = "prod")]
+pub const DEFAULT_MERCX_BACKEND: &str = "zoa6c-riaaa-aaaan-qzmta-cai";
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct KycConfig {
+    pub mercx_backend: Option<Principal>,
+}
+
+// Argument of init and post_upgrade, None keeps the stored value
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
+pub struct KycArgs {
+    pub mercx_backend: Option<Principal>,
+}
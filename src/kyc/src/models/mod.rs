@@ -1 +1,3 @@
 pub mod user;
+
+pub mod config;
\ No newline at end of file
@@ -1,10 +1,11 @@
 use candid::{CandidType, Decode, Encode, Principal};
 use ic_stable_structures::memory_manager::{MemoryId, MemoryManager, VirtualMemory};
-use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap, Storable};
+use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap, StableCell, Storable};
 use std::borrow::Cow;
 use std::cell::RefCell;
 use serde::{Serialize, Deserialize};
 
+use crate::models::config::KycConfig;
 use crate::models::user::User;
 
 type Memory = VirtualMemory<DefaultMemoryImpl>;
@@ -12,6 +13,7 @@ type Memory = VirtualMemory<DefaultMemoryImpl>;
 // Memory IDs for different maps
 const USERS_MEM_ID: MemoryId = MemoryId::new(0);
 const USERNAMES_MEM_ID: MemoryId = MemoryId::new(1);
+const CONFIG_MEM_ID: MemoryId = MemoryId::new(2);
 
 const MAX_VALUE_SIZE: u32 = 65536; // 64KB should be plenty for our structures
 
@@ -68,6 +70,11 @@ thread_local! {
         StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(USERNAMES_MEM_ID)))
     );
 
+    pub static CONFIG: RefCell<StableCell<KycConfig, Memory>> = RefCell::new(
+        StableCell::init(MEMORY_MANAGER.with(|m| m.borrow().get(CONFIG_MEM_ID)), KycConfig::default())
+            .expect("Failed to initialize KYC config")
+    );
+
 }
 
 // Implement Storable for our types
@@ -99,4 +106,19 @@ impl Storable for UserIdList {
         max_size: MAX_VALUE_SIZE,
         is_fixed_size: false,
     };
-}
\ No newline at end of file
+}
+
+impl Storable for KycConfig {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(Encode!(self).unwrap())
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        Decode!(bytes.as_ref(), Self).unwrap()
+    }
+
+    const BOUND: ic_stable_structures::storable::Bound = ic_stable_structures::storable::Bound::Bounded {
+        max_size: MAX_VALUE_SIZE,
+        is_fixed_size: false,
+    };
+}
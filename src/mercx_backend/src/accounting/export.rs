@@ -0,0 +1,315 @@
+use candid::{Nat, Principal};
+
+use super::export_args::{ExportArgs, ExportCursor, ExportFormat, ExportSource};
+use super::export_reply::ExportChunk;
+use crate::helpers::math_helpers::{nat_to_decimal_string, nat_to_decimals_f64};
+use crate::stable_memory::{TRANSFER_TS_INDEX, TX_TS_INDEX};
+use crate::stable_tx::stable_tx::StableTx;
+use crate::stable_tx::stable_tx_index::StableTxTsKey;
+use crate::stable_tx::tx_map;
+use crate::token::handlers::get_by_token_id;
+use crate::transfers::handlers::get_by_transfer_id;
+use crate::transfers::stable_transfer::StableTransfer;
+use crate::transfers::stable_transfer_index::StableTransferTsKey;
+use crate::transfers::tx_id::TxId;
+use crate::rbac::guards::is_admin;
+
+const DEFAULT_EXPORT_LIMIT: u32 = 500;
+const MAX_EXPORT_LIMIT: u32 = 1_000;
+
+const CSV_HEADER: &str =
+    "record,id,ts,type,direction,user,token_symbol,token_id,amount,usd_price,usd_value,block_index,request_id,pool_ids";
+
+/// One line of the export. direction is "in" for tokens the user sent to the DEX and "out" for tokens it sent back
+struct ExportRow {
+    record: &'static str, // "transfer" or "tx"
+    id: u64,
+    ts: u64,
+    row_type: String, // TransferType for transfers, tx kind for transactions
+    direction: &'static str,
+    user: Option<Principal>,
+    token_symbol: String,
+    token_id: u32,
+    amount: String, // decimals-adjusted
+    usd_price: Option<f64>,
+    usd_value: Option<f64>,
+    block_index: Option<String>,
+    request_id: Option<u64>,
+    pool_ids: Vec<u32>,
+}
+
+/// Export transfers or transactions with from_ts <= ts < to_ts as CSV or JSON, one chunk per call
+#[ic_cdk::query(guard = "is_admin")]
+fn export_accounting(args: ExportArgs) -> Result<ExportChunk, String> {
+    if args.from_ts >= args.to_ts {
+        Err("❌ from_ts must be before to_ts")?
+    }
+    let limit = args.limit.unwrap_or(DEFAULT_EXPORT_LIMIT).clamp(1, MAX_EXPORT_LIMIT) as usize;
+    let start = args.cursor.clone().unwrap_or(ExportCursor { ts: args.from_ts, id: 0 });
+
+    // one extra key tells where the next chunk starts
+    let mut keys = match args.source {
+        ExportSource::Transfers => TRANSFER_TS_INDEX.with(|m| {
+            m.borrow()
+                .range(StableTransferTsKey(start.ts, start.id)..StableTransferTsKey(args.to_ts, 0))
+                .take(limit + 1)
+                .map(|(key, _)| (key.0, key.1))
+                .collect::<Vec<_>>()
+        }),
+        ExportSource::Transactions => TX_TS_INDEX.with(|m| {
+            m.borrow()
+                .range(StableTxTsKey(start.ts, start.id)..StableTxTsKey(args.to_ts, 0))
+                .take(limit + 1)
+                .map(|(key, _)| (key.0, key.1))
+                .collect::<Vec<_>>()
+        }),
+    };
+    let next_cursor = if keys.len() > limit {
+        keys.pop().map(|(ts, id)| ExportCursor { ts, id })
+    } else {
+        None
+    };
+
+    let rows: Vec<ExportRow> = match args.source {
+        ExportSource::Transfers => keys
+            .iter()
+            .filter_map(|(_, transfer_id)| get_by_transfer_id(*transfer_id))
+            .map(|transfer| transfer_row(&transfer))
+            .collect(),
+        ExportSource::Transactions => keys
+            .iter()
+            .filter_map(|(_, tx_id)| tx_map::get_by_tx_id(*tx_id))
+            .flat_map(|tx| tx_rows(&tx))
+            .collect(),
+    };
+
+    let data = match args.format {
+        ExportFormat::Csv => to_csv(&rows, args.cursor.is_none()),
+        ExportFormat::Json => to_json(&rows),
+    };
+    Ok(ExportChunk {
+        data,
+        rows: rows.len() as u32,
+        next_cursor,
+    })
+}
+
+fn block_index(tx_id: &TxId) -> String {
+    match tx_id {
+        TxId::BlockIndex(block_index) => block_index.0.to_string(),
+        TxId::TransactionHash(hash) => hash.clone(),
+    }
+}
+
+/// Symbol, decimals-adjusted amount and amount as f64 of a token amount
+fn token_amount(token_id: u32, amount: &Nat) -> (String, String, Option<f64>) {
+    match get_by_token_id(token_id) {
+        Some(token) => (
+            token.symbol(),
+            nat_to_decimal_string(token.decimals(), amount),
+            nat_to_decimals_f64(token.decimals(), amount),
+        ),
+        // token was deleted, keep the raw amount
+        None => (String::new(), amount.0.to_string(), None),
+    }
+}
+
+fn transfer_row(transfer: &StableTransfer) -> ExportRow {
+    let (token_symbol, amount, amount_f64) = token_amount(transfer.token_id, &transfer.amount);
+    ExportRow {
+        record: "transfer",
+        id: transfer.transfer_id,
+        ts: transfer.ts,
+        row_type: format!("{:?}", transfer.transfer_type),
+        direction: if transfer.is_send { "in" } else { "out" },
+        user: transfer.account.map(|account| account.owner),
+        token_symbol,
+        token_id: transfer.token_id,
+        amount,
+        usd_price: transfer.usd_price,
+        usd_value: transfer.usd_price.zip(amount_f64).map(|(price, amount)| price * amount),
+        block_index: Some(block_index(&transfer.tx_id)),
+        request_id: transfer.request_id,
+        pool_ids: transfer.pool_ids.clone(),
+    }
+}
+
+/// One row per token the user paid or received. The price and block come from the linked ledger transfer,
+/// balance trades have none so the price falls back to the tx USD value when it covers a single token.
+fn tx_rows(tx: &StableTx) -> Vec<ExportRow> {
+    let row_type = match tx {
+        StableTx::Swap(_) => "Swap",
+        StableTx::AddPool(_) => "AddPool",
+        StableTx::AddLiquidity(_) => "AddLiquidity",
+        StableTx::RemoveLiquidity(_) => "RemoveLiquidity",
+    };
+    let transfers: Vec<StableTransfer> = tx.transfer_ids().iter().filter_map(|id| get_by_transfer_id(*id)).collect();
+    let (paid, received) = tx.token_amounts();
+    // the tx USD value prices what was paid, or what was received for liquidity removals
+    let priced_paid = !paid.is_empty();
+    let single_priced_leg = if priced_paid { paid.len() == 1 } else { received.len() == 1 };
+
+    let legs = paid.into_iter().map(|leg| (leg, true)).chain(received.into_iter().map(|leg| (leg, false)));
+    legs.map(|((token_id, amount), is_in)| {
+        let (token_symbol, amount_text, amount_f64) = token_amount(token_id, &amount);
+        let transfer = transfers.iter().find(|t| t.token_id == token_id && t.is_send == is_in);
+        let usd_price = transfer.and_then(|t| t.usd_price).or_else(|| {
+            if is_in != priced_paid || !single_priced_leg {
+                return None;
+            }
+            tx.usd_value().zip(amount_f64).filter(|(_, amount)| *amount > 0.0).map(|(value, amount)| value / amount)
+        });
+        ExportRow {
+            record: "tx",
+            id: tx.tx_id(),
+            ts: tx.ts(),
+            row_type: row_type.to_string(),
+            direction: if is_in { "in" } else { "out" },
+            user: Some(tx.user()),
+            token_symbol,
+            token_id,
+            amount: amount_text,
+            usd_price,
+            usd_value: usd_price.zip(amount_f64).map(|(price, amount)| price * amount),
+            block_index: transfer.map(|t| block_index(&t.tx_id)),
+            request_id: transfer.and_then(|t| t.request_id),
+            pool_ids: tx.pool_ids(),
+        }
+    })
+    .collect()
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn opt_to_string<T: ToString>(value: Option<T>) -> String {
+    value.map(|v| v.to_string()).unwrap_or_default()
+}
+
+fn to_csv(rows: &[ExportRow], with_header: bool) -> String {
+    let mut csv = String::new();
+    if with_header {
+        csv.push_str(CSV_HEADER);
+        csv.push('\n');
+    }
+    for row in rows {
+        let fields = [
+            row.record.to_string(),
+            row.id.to_string(),
+            row.ts.to_string(),
+            row.row_type.clone(),
+            row.direction.to_string(),
+            opt_to_string(row.user),
+            row.token_symbol.clone(),
+            row.token_id.to_string(),
+            row.amount.clone(),
+            opt_to_string(row.usd_price),
+            opt_to_string(row.usd_value),
+            row.block_index.clone().unwrap_or_default(),
+            opt_to_string(row.request_id),
+            row.pool_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(";"),
+        ];
+        csv.push_str(&fields.iter().map(|f| csv_field(f)).collect::<Vec<_>>().join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn json_string(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn json_f64(value: Option<f64>) -> String {
+    match value {
+        Some(v) if v.is_finite() => v.to_string(),
+        _ => "null".to_string(),
+    }
+}
+
+fn json_opt_string(value: Option<String>) -> String {
+    value.map(|v| json_string(&v)).unwrap_or_else(|| "null".to_string())
+}
+
+fn to_json(rows: &[ExportRow]) -> String {
+    let objects: Vec<String> = rows
+        .iter()
+        .map(|row| {
+            format!(
+                "{{\"record\":{},\"id\":{},\"ts\":{},\"type\":{},\"direction\":{},\"user\":{},\"token_symbol\":{},\"token_id\":{},\"amount\":{},\"usd_price\":{},\"usd_value\":{},\"block_index\":{},\"request_id\":{},\"pool_ids\":[{}]}}",
+                json_string(row.record),
+                row.id,
+                row.ts,
+                json_string(&row.row_type),
+                json_string(row.direction),
+                json_opt_string(row.user.map(|user| user.to_text())),
+                json_string(&row.token_symbol),
+                row.token_id,
+                json_string(&row.amount),
+                json_f64(row.usd_price),
+                json_f64(row.usd_value),
+                json_opt_string(row.block_index.clone()),
+                row.request_id.map(|id| id.to_string()).unwrap_or_else(|| "null".to_string()),
+                row.pool_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(","),
+            )
+        })
+        .collect();
+    format!("[{}]", objects.join(","))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row() -> ExportRow {
+        ExportRow {
+            record: "transfer",
+            id: 7,
+            ts: 1,
+            row_type: "Swap".to_string(),
+            direction: "in",
+            user: None,
+            token_symbol: "A,\"B\"".to_string(),
+            token_id: 2,
+            amount: nat_to_decimal_string(8, &Nat::from(150_000_000u64)),
+            usd_price: Some(2.0),
+            usd_value: None,
+            block_index: Some("42".to_string()),
+            request_id: None,
+            pool_ids: vec![1, 3],
+        }
+    }
+
+    #[test]
+    fn csv_quotes_fields_and_joins_pool_ids() {
+        let csv = to_csv(&[row()], false);
+        assert_eq!(csv, "transfer,7,1,Swap,in,,\"A,\"\"B\"\"\",2,1.5,2,,42,,1;3\n");
+        assert!(to_csv(&[], true).starts_with("record,id,ts"));
+    }
+
+    #[test]
+    fn json_escapes_strings_and_writes_nulls() {
+        let json = to_json(&[row()]);
+        assert!(json.starts_with("[{\"record\":\"transfer\",\"id\":7,"));
+        assert!(json.contains("\"token_symbol\":\"A,\\\"B\\\"\""));
+        assert!(json.contains("\"amount\":\"1.5\",\"usd_price\":2,\"usd_value\":null"));
+        assert!(json.ends_with("\"pool_ids\":[1,3]}]"));
+    }
+}
@@ -0,0 +1,31 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportSource {
+    Transfers,    // one row per ledger transfer
+    Transactions, // one row per token leg of a swap or liquidity tx
+}
+
+#[derive(CandidType, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+/// Position in the time index, (ts, id) of the next record to export
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExportCursor {
+    pub ts: u64,
+    pub id: u64,
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct ExportArgs {
+    pub source: ExportSource,
+    pub format: ExportFormat,
+    pub from_ts: u64, // inclusive
+    pub to_ts: u64,   // exclusive
+    pub cursor: Option<ExportCursor>, // None for the first chunk
+    pub limit: Option<u32>,           // records per chunk, a transaction can give more than one row
+}
@@ -0,0 +1,14 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+use super::export_args::ExportCursor;
+
+/// One chunk of an export, oldest first.
+/// CSV chunks only carry the header line when exported without a cursor, so chunks can be concatenated.
+/// JSON chunks are each a complete array of row objects.
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct ExportChunk {
+    pub data: String,
+    pub rows: u32,
+    pub next_cursor: Option<ExportCursor>, // pass back as cursor for the next chunk, None when the range is done
+}
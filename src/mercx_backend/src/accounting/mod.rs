@@ -0,0 +1,3 @@
+pub mod export_args;
+pub mod export_reply;
+pub mod export;
@@ -7,10 +7,10 @@ use crate::transfers::tx_id::TxId;
 /// Used in StableRequest
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
 pub struct AddLiquidityArgs {
-    pub token_0: String,
+    pub token_0: String, // symbol or IC.<canister_id>
     pub amount_0: Nat,
     pub tx_id_0: Option<TxId>,
-    pub token_1: String,
+    pub token_1: String, // symbol or IC.<canister_id>
     pub amount_1: Nat,
     pub tx_id_1: Option<TxId>,
 }
\ No newline at end of file
@@ -0,0 +1,90 @@
+use candid::Nat;
+
+use crate::add_liquidity::add_liquidity_args::AddLiquidityArgs;
+use crate::add_liquidity::add_liquidity_reply::AddLiquidityReply;
+use crate::add_liquidity::add_liquidity_reply_helpers::to_add_liquidity_reply;
+use crate::add_liquidity::add_liquidity_transfer_from::{calculate_amounts, update_liquidity_pool};
+use crate::balances::balance_map::{credit, debit};
+use crate::helpers::math_helpers::{nat_is_zero, nat_subtract, nat_zero};
+use crate::ic::general::get_time;
+use crate::ic::id::caller;
+use crate::kyc::kyc_id::get_user_by_caller;
+use crate::pool_analytics::analytics_storage::record_pool_snapshot2;
+use crate::stable_tx::{add_liquidity_tx::AddLiquidityTx, stable_tx::StableTx, tx_map};
+use crate::circuit_breaker::breaker::liquidity_open;
+use crate::compliance::compliance_policy::Operation;
+use crate::compliance::policy as compliance;
+use crate::compliance::rules;
+
+/// Add liquidity settled against the caller's internal balance, no ledger calls.
+/// Only the amounts the pool ratio accepts are kept, the rest stays in the internal balance.
+#[ic_cdk::update(guard = "liquidity_open")]
+pub async fn add_liquidity_with_balance(args: AddLiquidityArgs) -> Result<AddLiquidityReply, String> {
+    if nat_is_zero(&args.amount_0) || nat_is_zero(&args.amount_1) {
+        Err("Invalid zero amounts".to_string())?
+    }
+    if args.tx_id_0.is_some() || args.tx_id_1.is_some() {
+        Err("Tx_id_0 and Tx_id_1 not supported for balance liquidity".to_string())?
+    }
+
+    let (pool, add_amount_0, add_amount_1, _) = calculate_amounts(&args.token_0, &args.amount_0, &args.token_1, &args.amount_1)?;
+    let token_0 = pool.token_0();
+    if !token_0.is_active() {
+        Err("Token_0 is suspended or removed".to_string())?
+    }
+    let token_1 = pool.token_1();
+    if !token_1.is_active() {
+        Err("Token_1 is suspended or removed".to_string())?
+    }
+
+    compliance::check(Operation::AddLiquidity, caller(), &[&token_0, &token_1], None).await?;
+    rules::check_lockup(&token_0, caller())?;
+    rules::check_lockup(&token_1, caller())?;
+
+    let user_id = get_user_by_caller()
+        .await
+        .map_err(|e| format!("KYC lookup failed: {}", e))?
+        .ok_or("User not found. Please sign up in KYC first.")?
+        .user_id;
+
+    // the KYC call above is the last await before the pool update, debit after it
+    let owner = caller();
+    let ts = get_time();
+    debit(owner, token_0.token_id(), &add_amount_0, ts)?;
+    if let Err(e) = debit(owner, token_1.token_id(), &add_amount_1, ts) {
+        credit(owner, token_0.token_id(), &add_amount_0, ts);
+        Err(e)?
+    }
+
+    let (pool, amount_0, amount_1, add_lp_token_amount) = match update_liquidity_pool(user_id, &pool, &add_amount_0, &add_amount_1, ts).await {
+        Ok(result) => result,
+        Err(e) => {
+            credit(owner, token_0.token_id(), &add_amount_0, ts);
+            credit(owner, token_1.token_id(), &add_amount_1, ts);
+            Err(format!("failed. {}", e))?
+        }
+    };
+
+    // keep whatever the re-calculated ratio did not use
+    credit(owner, token_0.token_id(), &excess(&add_amount_0, &amount_0), ts);
+    credit(owner, token_1.token_id(), &excess(&add_amount_1, &amount_1), ts);
+    tx_map::record(&StableTx::AddLiquidity(AddLiquidityTx::new_success(
+        owner,
+        pool.pool_id,
+        &token_0,
+        &amount_0,
+        &token_1,
+        &amount_1,
+        &add_lp_token_amount,
+        &[],
+        ts,
+    )));
+
+    let _ = record_pool_snapshot2(pool.pool_id).await;
+
+    Ok(to_add_liquidity_reply(&pool, &token_0, &token_1, add_lp_token_amount, &[]))
+}
+
+fn excess(debited: &Nat, added: &Nat) -> Nat {
+    nat_subtract(debited, added).unwrap_or_else(nat_zero)
+}
@@ -1,6 +1,6 @@
 use candid::{Nat,Principal};
 use crate::helpers::math_helpers::{
-    nat_add, nat_divide, nat_is_zero, nat_multiply, nat_to_decimal_precision,nat_sqrt
+    nat_add, nat_divide, nat_is_zero, nat_multiply, nat_to_decimal_precision,nat_sqrt, nat_subtract, nat_zero
 };
 use crate::add_liquidity::add_liquidity_args::AddLiquidityArgs;
 use crate::add_liquidity::add_liquidity_reply::AddLiquidityReply;
@@ -10,27 +10,34 @@ use crate::ic::id::caller_id;
 use crate::pool::add_pool_arg::{return_token, transfer_from_token};
 use crate::pool::handlers;
 use crate::stable_mercx_settings::mercx_settings_map;
-use crate::token::handlers::exists_by_canister_id;
 use crate::StablePool;
 use icrc_ledger_types::icrc1::account::Account;
 use crate::transfers::tx_id::TxId;
 use crate::StableToken;
 use crate::transfers::stable_transfer::{StableTransfer,TransferType};
 use crate::ic::verify_transfer::verify_transfer;
-use crate::transfers::handlers::{insert,exist};
+use crate::transfers::handlers::{insert,exist,link};
 use crate::kyc::kyc_id::get_user_by_caller;
+use crate::stable_tx::{add_liquidity_tx::AddLiquidityTx, stable_tx::StableTx, tx_map};
  use crate::stable_lp_token::lp_token_map;
  use crate::StableLPToken;
  use crate::lp_metadata::stable_lp_metadata::LP_DECIMALS;
  use crate::pool_analytics::analytics_storage::record_pool_snapshot2;
-#[ic_cdk::update]
+use crate::circuit_breaker::breaker::liquidity_open;
+use crate::compliance::compliance_policy::Operation;
+use crate::compliance::policy as compliance;
+use crate::compliance::rules;
+#[ic_cdk::update(guard = "liquidity_open")]
 pub async fn add_liquidity_transfer_from(
     args: AddLiquidityArgs,
 ) -> Result<AddLiquidityReply, String> {
     let (user_id, pool, add_amount_0, add_amount_1,token_0, tx_id_0, token_1, tx_id_1) = check_arguments(&args).await?;
     let ts = get_time();
+    let mut transfer_ids = Vec::new();
 
-    let result = match process_add_liquidity(user_id,&pool, &add_amount_0, &add_amount_1,&token_0, tx_id_0.as_ref(), &token_1 ,tx_id_1.as_ref(),ts).await {
+    let result = process_add_liquidity(user_id,&pool, &add_amount_0, &add_amount_1,&token_0, tx_id_0.as_ref(), &token_1 ,tx_id_1.as_ref(), &mut transfer_ids, ts).await;
+    link(&transfer_ids, &[pool.pool_id]);
+    let result = match result {
         Ok(reply) => Ok({
             let _ = record_pool_snapshot2(reply.pool_id).await;
             reply}),
@@ -40,6 +47,7 @@ pub async fn add_liquidity_transfer_from(
     result
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn process_add_liquidity(
      user_id: u32,
     pool: &StablePool,
@@ -49,6 +57,7 @@ async fn process_add_liquidity(
     tx_id_0: Option<&Nat>,
     token_1: &StableToken,
     tx_id_1: Option<&Nat>,
+    transfer_ids: &mut Vec<u64>,
     ts: u64,
 ) -> Result<AddLiquidityReply, String> {
     // Token0
@@ -58,11 +67,10 @@ async fn process_add_liquidity(
 
     let caller_id = caller_id();
     let mercx_backend = mercx_settings_map::get().mercx_backend;
-    let mut transfer_ids = Vec::new();
 
     let transfer_0 = match tx_id_0 {
         Some(block_id) => {
-            verify_transfer_token(token_0, block_id, add_amount_0, &mut transfer_ids, ts).await
+            verify_transfer_token(token_0, block_id, add_amount_0, transfer_ids, ts).await
         }
         None => {
             transfer_from_token(
@@ -70,7 +78,7 @@ async fn process_add_liquidity(
                 token_0,
                 add_amount_0,
                 &mercx_backend,
-                &mut transfer_ids,
+                transfer_ids,
                 ts,
             )
             .await
@@ -81,7 +89,7 @@ async fn process_add_liquidity(
 
     let transfer_1 = match tx_id_1 {
         Some(block_id) => {
-            verify_transfer_token(token_1, block_id, add_amount_1, &mut transfer_ids, ts).await
+            verify_transfer_token(token_1, block_id, add_amount_1, transfer_ids, ts).await
         }
         None => {
             //  if transfer_token_0 failed, no need to icrc2_transfer_from token_1
@@ -93,7 +101,7 @@ async fn process_add_liquidity(
                     token_1,
                     add_amount_1,
                     &mercx_backend,
-                    &mut transfer_ids,
+                    transfer_ids,
                     ts,
                 )
                 .await
@@ -108,7 +116,7 @@ async fn process_add_liquidity(
     pool,
             add_amount_0,
             add_amount_1,
-            &mut transfer_ids,
+            transfer_ids,
             ts,
             &transfer_0,
             &transfer_1,    
@@ -123,7 +131,7 @@ async fn process_add_liquidity(
 
 
     // re-calculate with latest pool state and make sure amounts are valid
-    let (pool, _, _,add_lp_token_amount) = match update_liquidity_pool(user_id,pool, add_amount_0, add_amount_1 ,ts).await {
+    let (pool, amount_0, amount_1, add_lp_token_amount) = match update_liquidity_pool(user_id,pool, add_amount_0, add_amount_1 ,ts).await {
         Ok((pool, amount_0, amount_1,add_lp_token_amount)) => (pool, amount_0, amount_1,add_lp_token_amount),
         Err(err) => {
 
@@ -135,7 +143,7 @@ async fn process_add_liquidity(
                 pool,
                 add_amount_0,
                 add_amount_1,
-                &mut transfer_ids,
+                transfer_ids,
                 ts,
                 &transfer_0,
                 &transfer_1,  
@@ -147,16 +155,31 @@ async fn process_add_liquidity(
            &token_0.symbol(),
            &token_1.canister_id().expect("Missing canister_id").to_string(),
            &token_1.symbol(),
-           &transfer_ids));
+           transfer_ids));
         }
     };
 
+    // the pool ratio may have moved since the amounts were calculated, refund what was received but not added
+    return_excess(&caller_id, token_0, add_amount_0, &amount_0, transfer_ids, ts).await;
+    return_excess(&caller_id, token_1, add_amount_1, &amount_1, transfer_ids, ts).await;
+    tx_map::record(&StableTx::AddLiquidity(AddLiquidityTx::new_success(
+        caller_id.owner,
+        pool.pool_id,
+        token_0,
+        &amount_0,
+        token_1,
+        &amount_1,
+        &add_lp_token_amount,
+        transfer_ids,
+        ts,
+    )));
+
     Ok(to_add_liquidity_reply(
         &pool,
         &token_0,
         &token_1,
        add_lp_token_amount,
-        &transfer_ids,
+        transfer_ids,
     ))
 }
 
@@ -167,46 +190,50 @@ async fn check_arguments(args: &AddLiquidityArgs) -> Result<(u32,StablePool, Nat
         Err("Invalid zero amounts".to_string())?
     }
 
-    // check to make sure tx_id_0 and tx_id_1 is not specified
-    if args.tx_id_0.is_some() || args.tx_id_1.is_some() {
-        Err("Tx_id_0 and Tx_id_1 not supported".to_string())?
-    }
-
     // add_amount_0 and add_amount_1 are the amounts to be added to the pool with the current state
     // these are the amounts that will be transferred to the pool
     let (pool, add_amount_0, add_amount_1, _) =
         calculate_amounts(&args.token_0, &args.amount_0, &args.token_1, &args.amount_1)?;
 
     let token_0 = pool.token_0();
-    let token0 = exists_by_canister_id(token_0.canister_id().expect("Missing canister_id"));
-    if !token0 {
+    if !token_0.is_active() {
         Err("Token_0 is suspended or removed".to_string())?
     }
-    if !token_0.is_icrc2() {
-        Err("Token_0 must support ICRC2".to_string())?
+    // deposits verified by tx_id work for ICRC-1 only tokens
+    if args.tx_id_0.is_none() && !token_0.is_icrc2() {
+        Err("Token_0 must support ICRC2 or be deposited with tx_id_0".to_string())?
     }
 
     let token_1 = pool.token_1();
-    let token1 = exists_by_canister_id(token_1.canister_id().expect("Missing canister_id"));
-    if !token1 {
+    if !token_1.is_active() {
         Err("Token_1 is suspended or removed".to_string())?
     }
-    if !token_1.is_icrc2() {
-        Err("Token_1 must support ICRC2".to_string())?
+    if args.tx_id_1.is_none() && !token_1.is_icrc2() {
+        Err("Token_1 must support ICRC2 or be deposited with tx_id_1".to_string())?
     }
+    compliance::check(Operation::AddLiquidity, ic_cdk::caller(), &[&token_0, &token_1], None).await?;
+    rules::check_lockup(&token_0, ic_cdk::caller())?;
+    rules::check_lockup(&token_1, ic_cdk::caller())?;
 
     //new
     // check tx_id_0 is valid block index Nat
     let tx_id_0 = match &args.tx_id_0 {
         Some(TxId::BlockIndex(tx_id)) => Some(tx_id.clone()),
-        _ => None,
+        Some(_) => Err("Unsupported tx_id_0".to_string())?,
+        None => None,
     };
 
     let tx_id_1 = match &args.tx_id_1 {
         Some(TxId::BlockIndex(tx_id)) => Some(tx_id.clone()),
-        _ => None,
+        Some(_) => Err("Unsupported tx_id_1".to_string())?,
+        None => None,
     };
 
+    // a deposit is verified for the full amount the user sent, any amount above the pool ratio is refunded
+    // after the pool is updated. transfer_from only pulls the amount the pool ratio needs
+    let add_amount_0 = if tx_id_0.is_some() { args.amount_0.clone() } else { add_amount_0 };
+    let add_amount_1 = if tx_id_1.is_some() { args.amount_1.clone() } else { add_amount_1 };
+
     // either tx_id_0 or tx_id_1 must be valid
     // if tx_id_0.is_none() && tx_id_1.is_none() {
     //     Err("Tx_id_0 or Tx_id_1 is required".to_string())?
@@ -421,6 +448,23 @@ async fn return_tokens(
     //let reply = to_add_liquidity_reply_failed(pool.pool_id, request_id, transfer_ids, ts);
 }
 
+/// refund received - added, skipped when the excess would not cover the ledger fee
+async fn return_excess(
+    to_principal_id: &Account,
+    token: &StableToken,
+    received_amount: &Nat,
+    added_amount: &Nat,
+    transfer_ids: &mut Vec<u64>,
+    ts: u64,
+) {
+    let excess = nat_subtract(received_amount, added_amount).unwrap_or_else(nat_zero);
+    if excess <= token.fee() {
+        return;
+    }
+    ic_cdk::println!("💬 Returning excess {} {}", excess, token.symbol());
+    return_token(to_principal_id, token, &excess, transfer_ids, ts).await;
+}
+
 async fn verify_transfer_token(
     token: &StableToken,
     tx_id: &Nat,
@@ -447,6 +491,10 @@ async fn verify_transfer_token(
                 tx_id: TxId::BlockIndex(tx_id.clone()),
                 transfer_type: TransferType::LiquidityAdd,      
                 ts,
+                account: Some(caller_id()),
+                pool_ids: Vec::new(),
+                request_id: None,
+                usd_price: None,
             });
             transfer_ids.push(transfer_id);
             Ok(())
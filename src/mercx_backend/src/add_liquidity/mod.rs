@@ -3,4 +3,5 @@ pub mod add_liquidity_args;
 pub mod add_liquidity_transfer_from;
 pub mod add_liquidity_reply_helpers;
 pub mod add_liquidity_amounts_reply;
-pub mod add_liquidity_amounts;
\ No newline at end of file
+pub mod add_liquidity_amounts;
+pub mod add_liquidity_balance;
@@ -0,0 +1,20 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+/// What one archive run moved
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveReport {
+    pub transfers_archived: u64,
+    pub snapshots_archived: u64,
+    pub more_pending: bool, // batch limit was hit, the next run continues
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveStats {
+    pub hot_transfers: u64,
+    pub archived_transfers: u64,
+    pub hot_snapshots: u64,
+    pub archived_snapshots: u64,
+    pub transfer_retention_days: u32,
+    pub snapshot_retention_days: u32,
+}
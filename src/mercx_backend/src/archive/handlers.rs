@@ -0,0 +1,165 @@
+use std::time::Duration;
+
+use super::archive_reply::{ArchiveReport, ArchiveStats};
+use crate::pool_analytics::stable_analytics::{
+    PoolAnalyticsId, PoolSnapshot, PoolSnapshotKey, StablePoolTimeSeries, DEFAULT_SNAPSHOT_RETENTION_DAYS,
+};
+use crate::stable_memory::{ANALYTICS_DATA, SNAPSHOT_ARCHIVE, TRANSFERS, TRANSFER_ARCHIVE};
+use crate::stable_mercx_settings::handlers::record_change;
+use crate::stable_mercx_settings::mercx_settings_map;
+use crate::transfers::stable_transfer::{StableTransfer, StableTransferId};
+use crate::rbac::guards::{is_admin, is_operator};
+
+pub const DEFAULT_TRANSFER_RETENTION_DAYS: u32 = 90;
+// bounds the instructions of one run, a follow-up run picks up the rest
+const ARCHIVE_BATCH_SIZE: usize = 1_000;
+const ARCHIVE_INTERVAL_SECS: u64 = 24 * 60 * 60;
+// delay between runs while a backlog drains
+const ARCHIVE_FOLLOW_UP_SECS: u64 = 60;
+const NANOSECONDS_PER_DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
+
+pub fn transfer_retention_days() -> u32 {
+    mercx_settings_map::get().transfer_retention_days.unwrap_or(DEFAULT_TRANSFER_RETENTION_DAYS)
+}
+
+pub fn snapshot_retention_days() -> u32 {
+    mercx_settings_map::get().snapshot_retention_days.unwrap_or(DEFAULT_SNAPSHOT_RETENTION_DAYS)
+}
+
+pub fn get_archived_transfer(transfer_id: u64) -> Option<StableTransfer> {
+    TRANSFER_ARCHIVE.with(|m| m.borrow().get(&StableTransferId(transfer_id)))
+}
+
+/// Archived snapshots of a pool from from_day on, oldest first
+pub fn get_archived_snapshots(pool_id: u32, from_day: u64) -> Vec<PoolSnapshot> {
+    SNAPSHOT_ARCHIVE.with(|m| {
+        m.borrow()
+            .range(PoolSnapshotKey(pool_id, from_day)..=PoolSnapshotKey(pool_id, u64::MAX))
+            .map(|(_, snapshot)| snapshot)
+            .collect()
+    })
+}
+
+/// Move transfers older than the retention period from TRANSFERS to TRANSFER_ARCHIVE.
+/// The tx_id, time and principal indexes are keyed by transfer_id and stay as they are, lookups
+/// fall back to the archive. Transfer ids grow with time so the scan stops at the first recent one.
+fn archive_transfers(now: u64) -> (u64, bool) {
+    let cutoff_ts = now.saturating_sub(transfer_retention_days() as u64 * NANOSECONDS_PER_DAY);
+    let old_transfers: Vec<StableTransfer> = TRANSFERS.with(|m| {
+        m.borrow()
+            .iter()
+            .map(|(_, transfer)| transfer)
+            .take_while(|transfer| transfer.ts < cutoff_ts)
+            .take(ARCHIVE_BATCH_SIZE + 1)
+            .collect()
+    });
+    let more_pending = old_transfers.len() > ARCHIVE_BATCH_SIZE;
+
+    let mut archived = 0;
+    for transfer in old_transfers.into_iter().take(ARCHIVE_BATCH_SIZE) {
+        let transfer_id = StableTransferId(transfer.transfer_id);
+        TRANSFER_ARCHIVE.with(|m| m.borrow_mut().insert(transfer_id.clone(), transfer));
+        TRANSFERS.with(|m| m.borrow_mut().remove(&transfer_id));
+        archived += 1;
+    }
+    (archived, more_pending)
+}
+
+/// Move the snapshots older than the retention period out of a pool series into SNAPSHOT_ARCHIVE.
+/// Called before a series is stored so ANALYTICS_DATA stays bounded. Returns how many were moved
+pub fn archive_pool_series(pool_series: &mut StablePoolTimeSeries, now: u64) -> u64 {
+    let current_day = StablePoolTimeSeries::get_day_number(now);
+    let cutoff_day = current_day.saturating_sub(snapshot_retention_days() as u64);
+    let old_snapshots = pool_series.split_off_before(cutoff_day);
+    let archived = old_snapshots.len() as u64;
+    SNAPSHOT_ARCHIVE.with(|m| {
+        let mut archive = m.borrow_mut();
+        for snapshot in old_snapshots {
+            archive.insert(PoolSnapshotKey(snapshot.pool_id, snapshot.day_number), snapshot);
+        }
+    });
+    archived
+}
+
+fn archive_snapshots(now: u64) -> u64 {
+    let pool_ids: Vec<PoolAnalyticsId> = ANALYTICS_DATA.with(|m| m.borrow().iter().map(|(k, _)| k).collect());
+    let mut archived = 0;
+    for pool_id in pool_ids {
+        let Some(mut pool_series) = ANALYTICS_DATA.with(|m| m.borrow().get(&pool_id)) else {
+            continue;
+        };
+        let moved = archive_pool_series(&mut pool_series, now);
+        if moved > 0 {
+            ANALYTICS_DATA.with(|m| m.borrow_mut().insert(pool_id, pool_series));
+            archived += moved;
+        }
+    }
+    archived
+}
+
+pub fn run_archive() -> ArchiveReport {
+    let now = ic_cdk::api::time();
+    let (transfers_archived, more_pending) = archive_transfers(now);
+    let snapshots_archived = archive_snapshots(now);
+    ArchiveReport {
+        transfers_archived,
+        snapshots_archived,
+        more_pending,
+    }
+}
+
+/// run_archive() and, while more is pending, another run after ARCHIVE_FOLLOW_UP_SECS
+/// so a large backlog drains in batches instead of one batch a day
+fn run_archive_and_follow_up() -> ArchiveReport {
+    let report = run_archive();
+    ic_cdk::println!(
+        "Archive run done: {} transfers, {} snapshots, more pending: {}",
+        report.transfers_archived,
+        report.snapshots_archived,
+        report.more_pending
+    );
+    if report.more_pending {
+        ic_cdk_timers::set_timer(Duration::from_secs(ARCHIVE_FOLLOW_UP_SECS), || {
+            run_archive_and_follow_up();
+        });
+    }
+    report
+}
+
+/// Start the daily archive run. Timers do not survive upgrades so this runs from init and post_upgrade.
+pub fn start_archive_timer() {
+    ic_cdk_timers::set_timer_interval(Duration::from_secs(ARCHIVE_INTERVAL_SECS), || {
+        run_archive_and_follow_up();
+    });
+}
+
+#[ic_cdk::update(guard = "is_operator")]
+fn archive_now() -> Result<ArchiveReport, String> {
+    Ok(run_archive_and_follow_up())
+}
+
+/// Set how many days transfers and pool snapshots stay in the hot store.
+/// Nothing is deleted, shortening a period only moves more records to the archive on the next run.
+#[ic_cdk::update(guard = "is_admin")]
+fn set_archive_retention(transfer_retention_days: u32, snapshot_retention_days: u32) -> Result<(), String> {
+    if transfer_retention_days == 0 || snapshot_retention_days == 0 {
+        Err("❌ Retention must be at least one day")?
+    }
+    let (old_transfer_days, old_snapshot_days) = (self::transfer_retention_days(), self::snapshot_retention_days());
+    mercx_settings_map::set_retention_days(transfer_retention_days, snapshot_retention_days);
+    record_change("transfer_retention_days", old_transfer_days, transfer_retention_days);
+    record_change("snapshot_retention_days", old_snapshot_days, snapshot_retention_days);
+    Ok(())
+}
+
+#[ic_cdk::query]
+fn get_archive_stats() -> ArchiveStats {
+    ArchiveStats {
+        hot_transfers: TRANSFERS.with(|m| m.borrow().len()),
+        archived_transfers: TRANSFER_ARCHIVE.with(|m| m.borrow().len()),
+        hot_snapshots: ANALYTICS_DATA.with(|m| m.borrow().iter().map(|(_, s)| s.snapshots.len() as u64).sum()),
+        archived_snapshots: SNAPSHOT_ARCHIVE.with(|m| m.borrow().len()),
+        transfer_retention_days: transfer_retention_days(),
+        snapshot_retention_days: snapshot_retention_days(),
+    }
+}
@@ -0,0 +1,2 @@
+pub mod archive_reply;
+pub mod handlers;
@@ -0,0 +1,88 @@
+use candid::{CandidType, IDLArgs, Principal};
+use serde::{Deserialize, Serialize};
+
+use super::stable_audit_entry::{StableAuditEntry, StableAuditId};
+use crate::rbac::guards::is_admin;
+use crate::stable_memory::AUDIT_LOG;
+
+const DEFAULT_AUDIT_LIMIT: u32 = 50;
+const MAX_AUDIT_LIMIT: u32 = 200;
+// logos and other large arguments are cut so one call can not bloat the log
+const MAX_ARGS_LEN: usize = 1_024;
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct AuditPage {
+    pub entries: Vec<StableAuditEntry>,
+    pub next_cursor: Option<u64>, // pass back as cursor for older entries, None when there are no more
+}
+
+/// Append an entry. Ids follow the last entry, not a settings counter, so no reset can rewind them
+pub fn record(caller: Principal, action: &str, args: String) -> u64 {
+    AUDIT_LOG.with(|m| {
+        let mut map = m.borrow_mut();
+        let audit_id = map.last_key_value().map(|(id, _)| id.0 + 1).unwrap_or(0);
+        let entry = StableAuditEntry {
+            audit_id,
+            caller,
+            action: action.to_string(),
+            args,
+            ts: ic_cdk::api::time(),
+        };
+        map.insert(StableAuditId(audit_id), entry);
+        audit_id
+    })
+}
+
+/// Record the current call with its method name and arguments, called by the role guards
+pub fn record_call() {
+    // queries can not keep the entry anyway
+    if !ic_cdk::api::in_replicated_execution() {
+        return;
+    }
+    let args = match IDLArgs::from_bytes(&ic_cdk::api::call::arg_data_raw()) {
+        Ok(args) => truncate(args.to_string()),
+        Err(e) => format!("<undecodable: {}>", e),
+    };
+    record(ic_cdk::caller(), &ic_cdk::api::call::method_name(), args);
+}
+
+fn truncate(mut args: String) -> String {
+    if args.len() > MAX_ARGS_LEN {
+        let mut end = MAX_ARGS_LEN;
+        while !args.is_char_boundary(end) {
+            end -= 1;
+        }
+        args.truncate(end);
+        args.push('…');
+    }
+    args
+}
+
+/// Audit entries newest first
+#[ic_cdk::query(guard = "is_admin")]
+fn get_audit_log(cursor: Option<u64>, limit: Option<u32>) -> AuditPage {
+    let limit = limit.unwrap_or(DEFAULT_AUDIT_LIMIT).clamp(1, MAX_AUDIT_LIMIT) as usize;
+    let mut entries: Vec<StableAuditEntry> = AUDIT_LOG.with(|m| {
+        let map = m.borrow();
+        match cursor {
+            Some(cursor) => map.range(..=StableAuditId(cursor)).rev().take(limit + 1).map(|(_, e)| e).collect(),
+            None => map.iter().rev().take(limit + 1).map(|(_, e)| e).collect(),
+        }
+    });
+    let next_cursor = if entries.len() > limit { entries.pop().map(|e| e.audit_id) } else { None };
+    AuditPage { entries, next_cursor }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_cuts_long_args_on_a_char_boundary() {
+        assert_eq!(truncate("(42 : nat32)".to_string()), "(42 : nat32)");
+        let long = "é".repeat(MAX_ARGS_LEN);
+        let cut = truncate(long);
+        assert!(cut.len() <= MAX_ARGS_LEN + '…'.len_utf8());
+        assert!(cut.ends_with('…'));
+    }
+}
@@ -0,0 +1,2 @@
+pub mod stable_audit_entry;
+pub mod audit_log;
@@ -0,0 +1,31 @@
+use candid::{CandidType, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+use crate::stable_schema::envelope::impl_versioned_cbor;
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableAuditId(pub u64);
+
+impl Storable for StableAuditId {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<'_, [u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// One privileged call, written when the role guard lets it through
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableAuditEntry {
+    pub audit_id: u64,
+    pub caller: Principal,
+    pub action: String, // endpoint name, or init / post_upgrade
+    pub args: String,   // Candid text of the call arguments, record fields show as label hashes
+    pub ts: u64,
+}
+
+impl_versioned_cbor!(StableAuditEntry);
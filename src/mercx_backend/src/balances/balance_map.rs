@@ -0,0 +1,59 @@
+use candid::{Nat, Principal};
+
+use super::balance_reply::UserBalanceReply;
+use super::stable_balance::{StableBalance, StableBalanceId};
+use crate::helpers::math_helpers::{nat_add, nat_is_zero, nat_subtract, nat_zero};
+use crate::ic::id::caller;
+use crate::stable_memory::USER_BALANCES;
+use crate::token::handlers::get_by_token_id;
+
+pub fn get_balance(owner: Principal, token_id: u32) -> Nat {
+    USER_BALANCES
+        .with(|m| m.borrow().get(&StableBalanceId(owner, token_id)))
+        .map_or_else(nat_zero, |balance| balance.amount)
+}
+
+pub fn credit(owner: Principal, token_id: u32, amount: &Nat, ts: u64) {
+    if nat_is_zero(amount) {
+        return;
+    }
+    let amount = nat_add(&get_balance(owner, token_id), amount);
+    USER_BALANCES.with(|m| m.borrow_mut().insert(StableBalanceId(owner, token_id), StableBalance { amount, ts }));
+}
+
+/// debit is done before any await so a concurrent call sees the reduced balance
+pub fn debit(owner: Principal, token_id: u32, amount: &Nat, ts: u64) -> Result<(), String> {
+    let balance = get_balance(owner, token_id);
+    let amount = nat_subtract(&balance, amount)
+        .ok_or_else(|| format!("Insufficient balance. {} available, {} required", balance, amount))?;
+    USER_BALANCES.with(|m| {
+        let mut map = m.borrow_mut();
+        if nat_is_zero(&amount) {
+            map.remove(&StableBalanceId(owner, token_id));
+        } else {
+            map.insert(StableBalanceId(owner, token_id), StableBalance { amount, ts });
+        }
+    });
+    Ok(())
+}
+
+pub fn to_user_balance_reply(token_id: u32, amount: Nat) -> Option<UserBalanceReply> {
+    let token = get_by_token_id(token_id)?;
+    Some(UserBalanceReply {
+        symbol: token.symbol,
+        canister_id: token.canister_id.to_string(),
+        amount,
+    })
+}
+
+/// internal balances of the caller
+#[ic_cdk::query]
+pub fn get_my_balances() -> Vec<UserBalanceReply> {
+    let owner = caller();
+    USER_BALANCES.with(|m| {
+        m.borrow()
+            .range(StableBalanceId(owner, 0)..=StableBalanceId(owner, u32::MAX))
+            .filter_map(|(id, balance)| to_user_balance_reply(id.1, balance.amount))
+            .collect()
+    })
+}
@@ -0,0 +1,9 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct UserBalanceReply {
+    pub symbol: String,
+    pub canister_id: String,
+    pub amount: Nat,
+}
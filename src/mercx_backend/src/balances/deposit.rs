@@ -0,0 +1,167 @@
+use candid::Principal;
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc1::transfer::TransferError;
+
+use super::balance_map::{credit, get_balance, to_user_balance_reply};
+use super::balance_reply::UserBalanceReply;
+use super::stable_balance::StableBalanceId;
+use super::stable_pending_deposit::StablePendingDeposit;
+use crate::block_log::dex_block::log_admin_action;
+use crate::helpers::math_helpers::nat_subtract;
+use crate::ic::general::get_time;
+use crate::ic::id::caller;
+use crate::ic::transfer::{icrc1_balance_of, icrc1_transfer_checked, TransferFailure};
+use crate::rbac::guards::is_admin;
+use crate::stable_memory::PENDING_DEPOSITS;
+use crate::stable_mercx_settings::mercx_settings_map;
+use crate::token::handlers::get_by_token;
+use crate::token::stable_token::StableToken;
+use crate::transfers::handlers as transfer_handlers;
+use crate::transfers::stable_transfer::{StableTransfer, TransferType};
+use crate::transfers::tx_id::TxId;
+
+/// Deterministic deposit subaccount of the backend for a user: [len, principal bytes, 0 padding]
+pub fn deposit_subaccount(owner: &Principal) -> [u8; 32] {
+    let bytes = owner.as_slice();
+    let mut subaccount = [0_u8; 32];
+    subaccount[0] = bytes.len() as u8;
+    subaccount[1..1 + bytes.len()].copy_from_slice(bytes);
+    subaccount
+}
+
+pub fn deposit_account(owner: &Principal) -> Account {
+    Account {
+        owner: mercx_settings_map::get().mercx_backend.owner,
+        subaccount: Some(deposit_subaccount(owner)),
+    }
+}
+
+/// Account the caller sends tokens to before calling notify_deposit
+#[ic_cdk::query]
+pub fn get_deposit_account() -> Result<Account, String> {
+    let owner = caller();
+    if owner == Principal::anonymous() {
+        Err("Anonymous user not allowed")?
+    }
+    Ok(deposit_account(&owner))
+}
+
+fn get_pending(owner: Principal, token_id: u32) -> Option<StablePendingDeposit> {
+    PENDING_DEPOSITS.with(|m| m.borrow().get(&StableBalanceId(owner, token_id)))
+}
+
+/// Removes the pending sweep, false if a concurrent call already settled it
+fn take_pending(owner: Principal, token_id: u32) -> bool {
+    PENDING_DEPOSITS.with(|m| m.borrow_mut().remove(&StableBalanceId(owner, token_id)).is_some())
+}
+
+/// Sweep the caller's deposit subaccount for a token into the backend and credit the internal balance.
+/// The whole subaccount balance less the ledger fee is credited. After an unknown outcome a second call
+/// sends the same sweep again instead of reading the balance
+#[ic_cdk::update]
+pub async fn notify_deposit(token: String) -> Result<UserBalanceReply, String> {
+    let owner = caller();
+    if owner == Principal::anonymous() {
+        Err("Anonymous user not allowed")?
+    }
+    // tokens already sent to the deposit subaccount are credited whatever the listing status,
+    // so they can always be withdrawn
+    let token = get_by_token(&token)?;
+
+    let pending = match get_pending(owner, token.token_id()) {
+        Some(pending) => pending,
+        None => {
+            let deposited = icrc1_balance_of(&token, &deposit_account(&owner))
+                .await
+                .map_err(|e| format!("Failed to read deposit balance: {}", e))?;
+            let amount = match nat_subtract(&deposited, &token.fee()) {
+                Some(amount) if amount > 0_u32 => amount,
+                _ => Err(format!("No {} deposit above the ledger fee found", token.symbol()))?,
+            };
+            // a concurrent notify that read the balance first already started the sweep, send that one
+            match get_pending(owner, token.token_id()) {
+                Some(pending) => pending,
+                None => {
+                    let pending = StablePendingDeposit {
+                        amount,
+                        created_at_time: get_time(),
+                    };
+                    PENDING_DEPOSITS
+                        .with(|m| m.borrow_mut().insert(StableBalanceId(owner, token.token_id()), pending.clone()));
+                    pending
+                }
+            }
+        }
+    };
+
+    send_sweep(owner, &token, &pending).await?;
+    to_user_balance_reply(token.token_id(), get_balance(owner, token.token_id())).ok_or("Token not found".to_string())
+}
+
+/// The internal balance is only credited once the ledger confirms the sweep. A rejected call may
+/// still have executed, so the sweep stays pending and the next notify_deposit is deduplicated by the ledger
+async fn send_sweep(owner: Principal, token: &StableToken, pending: &StablePendingDeposit) -> Result<(), String> {
+    let token_id = token.token_id();
+    let mercx_backend = mercx_settings_map::get().mercx_backend;
+    match icrc1_transfer_checked(
+        &pending.amount,
+        Some(deposit_subaccount(&owner)),
+        &mercx_backend,
+        token,
+        Some(pending.created_at_time),
+    )
+    .await
+    {
+        Ok(block_id) | Err(TransferFailure::Ledger(TransferError::Duplicate { duplicate_of: block_id })) => {
+            if take_pending(owner, token_id) {
+                credit(owner, token_id, &pending.amount, get_time());
+                let transfer_id = transfer_handlers::insert(&StableTransfer {
+                    transfer_id: 0,
+                    is_send: true,
+                    amount: pending.amount.clone(),
+                    token_id,
+                    tx_id: TxId::BlockIndex(block_id),
+                    transfer_type: TransferType::Deposit,
+                    ts: pending.created_at_time,
+                    account: Some(Account::from(owner)),
+                    pool_ids: Vec::new(),
+                    request_id: None,
+                    usd_price: None,
+                });
+                transfer_handlers::link(&[transfer_id], &[]);
+                ic_cdk::println!("💬 Credited {} {} to {}", pending.amount, token.symbol(), owner);
+            }
+            Ok(())
+        }
+        // past the ledger's deduplication window a retry proves nothing, an admin checks the ledger
+        Err(TransferFailure::Ledger(TransferError::TooOld)) => {
+            Err("❌ Pending deposit is too old to retry, an admin has to resolve it".to_string())
+        }
+        // nothing moved, the next notify_deposit reads the subaccount balance again
+        Err(TransferFailure::Ledger(e)) => {
+            take_pending(owner, token_id);
+            Err(format!("Failed to sweep deposit: {}", e))
+        }
+        Err(TransferFailure::Call(e)) => Err(format!(
+            "❌ Deposit sweep outcome unknown, call notify_deposit again. {}",
+            e
+        )),
+    }
+}
+
+/// Settles a pending deposit sweep that can no longer be retried. `executed` is what the ledger shows,
+/// true credits the internal balance
+#[ic_cdk::update(guard = "is_admin")]
+fn resolve_pending_deposit(owner: Principal, token: String, executed: bool) -> Result<(), String> {
+    let token = get_by_token(&token)?;
+    let pending = get_pending(owner, token.token_id()).ok_or("❌ No pending deposit")?;
+    take_pending(owner, token.token_id());
+    if executed {
+        credit(owner, token.token_id(), &pending.amount, get_time());
+    }
+    log_admin_action(
+        "resolve_pending_deposit",
+        &format!("{} {} {} executed={}", owner, token.symbol, pending.amount, executed),
+    );
+    Ok(())
+}
@@ -0,0 +1,7 @@
+pub mod stable_balance;
+pub mod balance_map;
+pub mod balance_reply;
+pub mod deposit;
+pub mod withdraw;
+pub mod stable_pending_withdrawal;
+pub mod stable_pending_deposit;
@@ -0,0 +1,28 @@
+use candid::{CandidType, Nat, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+use crate::stable_schema::envelope::impl_versioned_cbor;
+
+/// (owner, token_id) key of USER_BALANCES
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableBalanceId(pub Principal, pub u32);
+
+impl Storable for StableBalanceId {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<'_, [u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableBalance {
+    pub amount: Nat, // internal balance held by the backend for the owner
+    pub ts: u64,     // timestamp of the last update
+}
+
+impl_versioned_cbor!(StableBalance);
@@ -0,0 +1,13 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+use crate::stable_schema::envelope::impl_versioned_cbor;
+
+/// Deposit sweep sent from the owner's deposit subaccount whose ledger transfer has not been confirmed yet.
+/// Retries reuse created_at_time so the ledger deduplicates them
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StablePendingDeposit {
+    pub amount: Nat, // swept to the backend and credited, the subaccount balance less the ledger fee
+    pub created_at_time: u64,
+}
+
+impl_versioned_cbor!(StablePendingDeposit);
@@ -0,0 +1,14 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+use crate::stable_schema::envelope::impl_versioned_cbor;
+
+/// Withdrawal debited from the internal balance whose ledger transfer has not been confirmed yet.
+/// Retries reuse created_at_time so the ledger deduplicates them
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StablePendingWithdrawal {
+    pub amount: Nat,          // debited from the internal balance
+    pub amount_with_gas: Nat, // sent to the owner, amount less the ledger fee
+    pub created_at_time: u64,
+}
+
+impl_versioned_cbor!(StablePendingWithdrawal);
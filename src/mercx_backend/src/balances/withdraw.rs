@@ -0,0 +1,128 @@
+use candid::{Nat, Principal};
+use icrc_ledger_types::icrc1::transfer::TransferError;
+
+use super::balance_map::{credit, debit};
+use super::stable_balance::StableBalanceId;
+use super::stable_pending_withdrawal::StablePendingWithdrawal;
+use crate::block_log::dex_block::log_admin_action;
+use crate::compliance::rules;
+use crate::helpers::math_helpers::{nat_subtract, nat_zero};
+use crate::ic::address::Address;
+use crate::ic::general::get_time;
+use crate::ic::id::{caller, caller_id};
+use crate::ic::transfer::{icrc1_transfer_checked, TransferFailure};
+use crate::rbac::guards::is_admin;
+use crate::stable_memory::PENDING_WITHDRAWALS;
+use crate::token::handlers::get_by_token;
+use crate::token::stable_token::StableToken;
+use crate::transfers::handlers as transfer_handlers;
+use crate::transfers::stable_transfer::{StableTransfer, TransferType};
+use crate::transfers::tx_id::TxId;
+
+fn get_pending(owner: Principal, token_id: u32) -> Option<StablePendingWithdrawal> {
+    PENDING_WITHDRAWALS.with(|m| m.borrow().get(&StableBalanceId(owner, token_id)))
+}
+
+/// Removes the pending withdrawal, false if a concurrent call already settled it
+fn take_pending(owner: Principal, token_id: u32) -> bool {
+    PENDING_WITHDRAWALS.with(|m| m.borrow_mut().remove(&StableBalanceId(owner, token_id)).is_some())
+}
+
+/// Move `amount` of the caller's internal balance to the caller's wallet.
+/// The ledger fee is taken out of `amount`. Returns the ledger block index.
+#[ic_cdk::update]
+pub async fn withdraw(token: String, amount: Nat) -> Result<Nat, String> {
+    let owner = caller();
+    let token = get_by_token(&token)?;
+    let amount_with_gas = match nat_subtract(&amount, &token.fee()) {
+        Some(amount_with_gas) if amount_with_gas > 0_u32 => amount_with_gas,
+        _ => Err(format!("Withdraw amount must be above the ledger fee {}", token.fee()))?,
+    };
+
+    // a withdrawal moves tokens the caller already holds, so only the recipient rules apply
+    rules::check_recipient(&token, &Address::PrincipalId(caller_id()), &nat_zero()).await?;
+
+    if get_pending(owner, token.token_id()).is_some() {
+        Err(format!("❌ A {} withdrawal is still pending, call retry_withdraw first", token.symbol))?
+    }
+
+    let ts = get_time();
+    // debit before the ledger call so the balance can't be withdrawn twice
+    debit(owner, token.token_id(), &amount, ts)?;
+    let pending = StablePendingWithdrawal {
+        amount,
+        amount_with_gas,
+        created_at_time: ts,
+    };
+    PENDING_WITHDRAWALS.with(|m| m.borrow_mut().insert(StableBalanceId(owner, token.token_id()), pending.clone()));
+
+    send_withdrawal(owner, &token, &pending).await
+}
+
+/// Sends the caller's pending withdrawal of a token again after an unknown outcome
+#[ic_cdk::update]
+pub async fn retry_withdraw(token: String) -> Result<Nat, String> {
+    let owner = caller();
+    let token = get_by_token(&token)?;
+    let pending = get_pending(owner, token.token_id()).ok_or("❌ No pending withdrawal")?;
+    send_withdrawal(owner, &token, &pending).await
+}
+
+/// The balance is only restored when the ledger surely refused the transfer. A rejected call may
+/// still have executed, so the withdrawal stays pending and a retry is deduplicated by the ledger
+async fn send_withdrawal(owner: Principal, token: &StableToken, pending: &StablePendingWithdrawal) -> Result<Nat, String> {
+    let token_id = token.token_id();
+    let to = caller_id();
+    match icrc1_transfer_checked(&pending.amount_with_gas, None, &to, token, Some(pending.created_at_time)).await {
+        Ok(block_id) | Err(TransferFailure::Ledger(TransferError::Duplicate { duplicate_of: block_id })) => {
+            if take_pending(owner, token_id) {
+                let transfer_id = transfer_handlers::insert(&StableTransfer {
+                    transfer_id: 0,
+                    is_send: false,
+                    amount: pending.amount_with_gas.clone(),
+                    token_id,
+                    tx_id: TxId::BlockIndex(block_id.clone()),
+                    transfer_type: TransferType::Withdraw,
+                    ts: pending.created_at_time,
+                    account: Some(to),
+                    pool_ids: Vec::new(),
+                    request_id: None,
+                    usd_price: None,
+                });
+                transfer_handlers::link(&[transfer_id], &[]);
+            }
+            Ok(block_id)
+        }
+        // past the ledger's deduplication window a retry proves nothing, an admin checks the ledger
+        Err(TransferFailure::Ledger(TransferError::TooOld)) => {
+            Err("❌ Pending withdrawal is too old to retry, an admin has to resolve it".to_string())
+        }
+        Err(TransferFailure::Ledger(e)) => {
+            if take_pending(owner, token_id) {
+                credit(owner, token_id, &pending.amount, get_time());
+            }
+            Err(format!("Withdraw failed, balance restored. {}", e))
+        }
+        Err(TransferFailure::Call(e)) => Err(format!(
+            "❌ Withdraw outcome unknown, the balance stays reserved. Call retry_withdraw. {}",
+            e
+        )),
+    }
+}
+
+/// Settles a pending withdrawal that can no longer be retried. `executed` is what the ledger shows,
+/// false restores the internal balance
+#[ic_cdk::update(guard = "is_admin")]
+fn resolve_pending_withdrawal(owner: Principal, token: String, executed: bool) -> Result<(), String> {
+    let token = get_by_token(&token)?;
+    let pending = get_pending(owner, token.token_id()).ok_or("❌ No pending withdrawal")?;
+    take_pending(owner, token.token_id());
+    if !executed {
+        credit(owner, token.token_id(), &pending.amount, get_time());
+    }
+    log_admin_action(
+        "resolve_pending_withdrawal",
+        &format!("{} {} {} executed={}", owner, token.symbol, pending.amount, executed),
+    );
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use candid::Nat;
+use icrc_ledger_types::icrc::generic_value::{ICRC3Map, ICRC3Value};
+use serde_bytes::ByteBuf;
+
+use super::hash_tree::{self, Hash};
+use super::stable_block::{StableBlock, StableBlockId};
+use crate::stable_memory::BLOCKS;
+
+pub fn len() -> u64 {
+    BLOCKS.with(|m| m.borrow().len())
+}
+
+pub fn get(block_id: u64) -> Option<ICRC3Value> {
+    BLOCKS.with(|m| m.borrow().get(&StableBlockId(block_id)).map(|b| b.0))
+}
+
+/// Blocks in [start, start + length), stops at the end of the log
+pub fn get_range(start: u64, length: u64) -> Vec<(u64, ICRC3Value)> {
+    BLOCKS.with(|m| {
+        m.borrow()
+            .range(StableBlockId(start)..)
+            .take(length as usize)
+            .map(|(id, block)| (id.0, block.0))
+            .collect()
+    })
+}
+
+/// Index and hash of the last block, None while the log is empty
+pub fn tip() -> Option<(u64, Hash)> {
+    BLOCKS.with(|m| m.borrow().last_key_value().map(|(id, block)| (id.0, block.0.hash())))
+}
+
+/// Build a block chained to the current tip. The parent hash is left out of the first block
+pub fn new_block(btype: &str, ts: u64, tx: ICRC3Map, parent: Option<Hash>) -> ICRC3Value {
+    let mut block = ICRC3Map::new();
+    block.insert("btype".to_string(), ICRC3Value::Text(btype.to_string()));
+    block.insert("ts".to_string(), ICRC3Value::Nat(Nat::from(ts)));
+    if let Some(phash) = parent {
+        block.insert("phash".to_string(), ICRC3Value::Blob(ByteBuf::from(phash.to_vec())));
+    }
+    block.insert("tx".to_string(), ICRC3Value::Map(tx));
+    ICRC3Value::Map(block)
+}
+
+/// Append a block to the log and certify the new tip. Returns the block index
+pub fn append(btype: &str, tx: ICRC3Map) -> u64 {
+    let ts = ic_cdk::api::time();
+    let (block_id, block) = match tip() {
+        Some((last_id, last_hash)) => (last_id + 1, new_block(btype, ts, tx, Some(last_hash))),
+        None => (0, new_block(btype, ts, tx, None)),
+    };
+    BLOCKS.with(|m| m.borrow_mut().insert(StableBlockId(block_id), StableBlock(block)));
+    certify_tip();
+    block_id
+}
+
+/// Set the canister certified data to the root of the tip hash tree.
+/// Certified data does not survive upgrades so this is also called from post_upgrade
+pub fn certify_tip() {
+    if let Some((last_id, last_hash)) = tip() {
+        ic_cdk::api::set_certified_data(&hash_tree::tip_root_hash(last_id, &last_hash));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn blocks_are_chained_by_parent_hash() {
+        let first = new_block("swap", 1, ICRC3Map::new(), None);
+        let second = new_block("swap", 2, ICRC3Map::new(), Some(first.clone().hash()));
+
+        let ICRC3Value::Map(first_map) = &first else { panic!("block is not a map") };
+        assert!(!first_map.contains_key("phash"));
+
+        let ICRC3Value::Map(second_map) = second else { panic!("block is not a map") };
+        assert_eq!(
+            second_map.get("phash"),
+            Some(&ICRC3Value::Blob(ByteBuf::from(first.hash().to_vec())))
+        );
+    }
+}
@@ -0,0 +1,93 @@
+use candid::{Nat, Principal};
+use icrc_ledger_types::icrc::generic_value::{ICRC3Map, ICRC3Value};
+use serde_bytes::ByteBuf;
+
+use super::block_map;
+use crate::stable_tx::stable_tx::StableTx;
+
+pub const BTYPE_SWAP: &str = "swap";
+pub const BTYPE_ADD_POOL: &str = "add_pool";
+pub const BTYPE_ADD_LIQUIDITY: &str = "add_liquidity";
+pub const BTYPE_REMOVE_LIQUIDITY: &str = "remove_liquidity";
+pub const BTYPE_FEE_WITHDRAW: &str = "fee_withdraw";
+pub const BTYPE_ADMIN: &str = "admin";
+
+fn principal(p: Principal) -> ICRC3Value {
+    ICRC3Value::Blob(ByteBuf::from(p.as_slice().to_vec()))
+}
+
+fn nat(n: impl Into<Nat>) -> ICRC3Value {
+    ICRC3Value::Nat(n.into())
+}
+
+fn text(s: &str) -> ICRC3Value {
+    ICRC3Value::Text(s.to_string())
+}
+
+/// Block type and tx map of a swap or liquidity tx
+pub fn tx_to_block(tx: &StableTx) -> (&'static str, ICRC3Map) {
+    let mut map = ICRC3Map::new();
+    map.insert("tx_id".to_string(), nat(tx.tx_id()));
+    map.insert("user".to_string(), principal(tx.user()));
+    map.insert(
+        "pool_ids".to_string(),
+        ICRC3Value::Array(tx.pool_ids().into_iter().map(nat).collect()),
+    );
+
+    let btype = match tx {
+        StableTx::Swap(swap) => {
+            map.insert("pay_token_id".to_string(), nat(swap.pay_token_id));
+            map.insert("pay_amount".to_string(), nat(swap.pay_amount.clone()));
+            map.insert("receive_token_id".to_string(), nat(swap.receive_token_id));
+            map.insert("receive_amount".to_string(), nat(swap.receive_amount.clone()));
+            BTYPE_SWAP
+        }
+        StableTx::AddPool(add_pool) => {
+            map.insert("token_id_0".to_string(), nat(add_pool.token_id_0));
+            map.insert("amount_0".to_string(), nat(add_pool.amount_0.clone()));
+            map.insert("token_id_1".to_string(), nat(add_pool.token_id_1));
+            map.insert("amount_1".to_string(), nat(add_pool.amount_1.clone()));
+            map.insert("lp_token_amount".to_string(), nat(add_pool.add_lp_token_amount.clone()));
+            BTYPE_ADD_POOL
+        }
+        StableTx::AddLiquidity(add_liquidity) => {
+            map.insert("amount_0".to_string(), nat(add_liquidity.amount_0.clone()));
+            map.insert("amount_1".to_string(), nat(add_liquidity.amount_1.clone()));
+            map.insert("lp_token_amount".to_string(), nat(add_liquidity.add_lp_token_amount.clone()));
+            BTYPE_ADD_LIQUIDITY
+        }
+        StableTx::RemoveLiquidity(remove_liquidity) => {
+            map.insert("amount_0".to_string(), nat(remove_liquidity.amount_0.clone()));
+            map.insert("lp_fee_0".to_string(), nat(remove_liquidity.lp_fee_0.clone()));
+            map.insert("amount_1".to_string(), nat(remove_liquidity.amount_1.clone()));
+            map.insert("lp_fee_1".to_string(), nat(remove_liquidity.lp_fee_1.clone()));
+            map.insert("lp_token_amount".to_string(), nat(remove_liquidity.remove_lp_token_amount.clone()));
+            BTYPE_REMOVE_LIQUIDITY
+        }
+    };
+    (btype, map)
+}
+
+pub fn log_tx(tx: &StableTx) -> u64 {
+    let (btype, map) = tx_to_block(tx);
+    block_map::append(btype, map)
+}
+
+/// Canister funds sent out of the DEX, block_index is the index on the token's ledger
+pub fn log_fee_withdraw(to: Principal, ledger: Principal, amount: &Nat, block_index: &Nat) -> u64 {
+    let mut map = ICRC3Map::new();
+    map.insert("to".to_string(), principal(to));
+    map.insert("ledger".to_string(), principal(ledger));
+    map.insert("amount".to_string(), nat(amount.clone()));
+    map.insert("ledger_block".to_string(), nat(block_index.clone()));
+    block_map::append(BTYPE_FEE_WITHDRAW, map)
+}
+
+/// Admin change to tokens, pools or the whitelist, logged with the calling principal
+pub fn log_admin_action(action: &str, detail: &str) -> u64 {
+    let mut map = ICRC3Map::new();
+    map.insert("caller".to_string(), principal(ic_cdk::caller()));
+    map.insert("action".to_string(), text(action));
+    map.insert("detail".to_string(), text(detail));
+    block_map::append(BTYPE_ADMIN, map)
+}
@@ -0,0 +1,114 @@
+use serde_cbor::Value;
+use sha2::{Digest, Sha256};
+
+pub type Hash = [u8; 32];
+
+const LAST_BLOCK_HASH: &[u8] = b"last_block_hash";
+const LAST_BLOCK_INDEX: &[u8] = b"last_block_index";
+
+/// Minimal IC hash tree for the ICRC-3 tip, see the IC interface spec "Certificate" section.
+enum HashTree {
+    Fork(Box<HashTree>, Box<HashTree>),
+    Labeled(Vec<u8>, Box<HashTree>),
+    Leaf(Vec<u8>),
+}
+
+impl HashTree {
+    fn digest(&self) -> Hash {
+        let mut hasher = Sha256::new();
+        match self {
+            HashTree::Fork(left, right) => {
+                hasher.update(domain_sep("ic-hashtree-fork"));
+                hasher.update(left.digest());
+                hasher.update(right.digest());
+            }
+            HashTree::Labeled(label, subtree) => {
+                hasher.update(domain_sep("ic-hashtree-labeled"));
+                hasher.update(label);
+                hasher.update(subtree.digest());
+            }
+            HashTree::Leaf(value) => {
+                hasher.update(domain_sep("ic-hashtree-leaf"));
+                hasher.update(value);
+            }
+        }
+        hasher.finalize().into()
+    }
+
+    fn to_cbor(&self) -> Value {
+        match self {
+            HashTree::Fork(left, right) => Value::Array(vec![Value::Integer(1), left.to_cbor(), right.to_cbor()]),
+            HashTree::Labeled(label, subtree) => {
+                Value::Array(vec![Value::Integer(2), Value::Bytes(label.clone()), subtree.to_cbor()])
+            }
+            HashTree::Leaf(value) => Value::Array(vec![Value::Integer(3), Value::Bytes(value.clone())]),
+        }
+    }
+}
+
+fn domain_sep(s: &str) -> Vec<u8> {
+    let mut buf = vec![s.len() as u8];
+    buf.extend_from_slice(s.as_bytes());
+    buf
+}
+
+fn leb128(mut n: u64) -> Vec<u8> {
+    let mut buf = Vec::new();
+    loop {
+        let byte = (n & 0x7f) as u8;
+        n >>= 7;
+        if n == 0 {
+            buf.push(byte);
+            return buf;
+        }
+        buf.push(byte | 0x80);
+    }
+}
+
+// labels are in ascending order as the spec requires, "last_block_hash" < "last_block_index"
+fn tip_tree(last_block_index: u64, last_block_hash: &Hash) -> HashTree {
+    HashTree::Fork(
+        Box::new(HashTree::Labeled(LAST_BLOCK_HASH.to_vec(), Box::new(HashTree::Leaf(last_block_hash.to_vec())))),
+        Box::new(HashTree::Labeled(LAST_BLOCK_INDEX.to_vec(), Box::new(HashTree::Leaf(leb128(last_block_index))))),
+    )
+}
+
+/// Root hash to pass to set_certified_data
+pub fn tip_root_hash(last_block_index: u64, last_block_hash: &Hash) -> Hash {
+    tip_tree(last_block_index, last_block_hash).digest()
+}
+
+/// CBOR encoded tree returned as the hash_tree of icrc3_get_tip_certificate
+pub fn tip_hash_tree(last_block_index: u64, last_block_hash: &Hash) -> Vec<u8> {
+    serde_cbor::to_vec(&tip_tree(last_block_index, last_block_hash).to_cbor()).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leb128_matches_spec_examples() {
+        assert_eq!(leb128(0), vec![0x00]);
+        assert_eq!(leb128(127), vec![0x7f]);
+        assert_eq!(leb128(128), vec![0x80, 0x01]);
+        assert_eq!(leb128(624485), vec![0xe5, 0x8e, 0x26]);
+    }
+
+    #[test]
+    fn tip_tree_encodes_fork_of_two_labeled_leaves() {
+        let hash = [7u8; 32];
+        let tree: Value = serde_cbor::from_slice(&tip_hash_tree(5, &hash)).unwrap();
+        let Value::Array(fork) = tree else { panic!("not a fork") };
+        assert_eq!(fork[0], Value::Integer(1));
+        assert_eq!(
+            fork[2],
+            Value::Array(vec![
+                Value::Integer(2),
+                Value::Bytes(LAST_BLOCK_INDEX.to_vec()),
+                Value::Array(vec![Value::Integer(3), Value::Bytes(vec![5])]),
+            ])
+        );
+        assert_ne!(tip_root_hash(5, &hash), tip_root_hash(6, &hash));
+    }
+}
@@ -0,0 +1,65 @@
+use candid::Nat;
+use icrc_ledger_types::icrc3::archive::{GetArchivesArgs, ICRC3ArchiveInfo};
+use icrc_ledger_types::icrc3::blocks::{
+    BlockWithId, GetBlocksRequest, GetBlocksResult, ICRC3DataCertificate, SupportedBlockType,
+};
+use serde_bytes::ByteBuf;
+
+use super::block_map;
+use super::dex_block::{
+    BTYPE_ADD_LIQUIDITY, BTYPE_ADD_POOL, BTYPE_ADMIN, BTYPE_FEE_WITHDRAW, BTYPE_REMOVE_LIQUIDITY, BTYPE_SWAP,
+};
+use super::hash_tree;
+use crate::helpers::math_helpers::nat_to_u64;
+
+const MAX_BLOCKS_PER_RESPONSE: u64 = 100;
+
+#[ic_cdk::query]
+fn icrc3_get_blocks(args: Vec<GetBlocksRequest>) -> GetBlocksResult {
+    let mut blocks = Vec::new();
+    for arg in args {
+        let remaining = MAX_BLOCKS_PER_RESPONSE - blocks.len() as u64;
+        if remaining == 0 {
+            break;
+        }
+        let (Some(start), Some(length)) = (nat_to_u64(&arg.start), nat_to_u64(&arg.length)) else {
+            continue;
+        };
+        for (id, block) in block_map::get_range(start, length.min(remaining)) {
+            blocks.push(BlockWithId { id: Nat::from(id), block });
+        }
+    }
+
+    GetBlocksResult {
+        log_length: Nat::from(block_map::len()),
+        blocks,
+        // every block is kept in this canister
+        archived_blocks: Vec::new(),
+    }
+}
+
+#[ic_cdk::query]
+fn icrc3_get_tip_certificate() -> Option<ICRC3DataCertificate> {
+    let certificate = ic_cdk::api::data_certificate()?;
+    let (last_id, last_hash) = block_map::tip()?;
+    Some(ICRC3DataCertificate {
+        certificate: ByteBuf::from(certificate),
+        hash_tree: ByteBuf::from(hash_tree::tip_hash_tree(last_id, &last_hash)),
+    })
+}
+
+#[ic_cdk::query]
+fn icrc3_get_archives(_args: GetArchivesArgs) -> Vec<ICRC3ArchiveInfo> {
+    Vec::new()
+}
+
+#[ic_cdk::query]
+fn icrc3_supported_block_types() -> Vec<SupportedBlockType> {
+    [BTYPE_SWAP, BTYPE_ADD_POOL, BTYPE_ADD_LIQUIDITY, BTYPE_REMOVE_LIQUIDITY, BTYPE_FEE_WITHDRAW, BTYPE_ADMIN]
+        .iter()
+        .map(|btype| SupportedBlockType {
+            block_type: btype.to_string(),
+            url: "https://github.com/dfinity/ICRC-1/blob/main/standards/ICRC-3/README.md".to_string(),
+        })
+        .collect()
+}
@@ -0,0 +1,5 @@
+pub mod stable_block;
+pub mod hash_tree;
+pub mod block_map;
+pub mod dex_block;
+pub mod icrc3;
@@ -0,0 +1,49 @@
+use candid::CandidType;
+use ic_stable_structures::{storable::Bound, Storable};
+use icrc_ledger_types::icrc::generic_value::ICRC3Value;
+use serde::{Deserialize, Serialize};
+use crate::stable_schema::envelope::{self, Versioned};
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableBlockId(pub u64);
+
+impl Storable for StableBlockId {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<'_, [u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// One ICRC-3 block of the DEX event log, Candid encoded so the stored value is exactly what icrc3_get_blocks returns
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableBlock(pub ICRC3Value);
+
+impl Versioned for StableBlock {
+    const NAME: &'static str = "StableBlock";
+    const VERSION: u8 = 1;
+
+    fn encode_payload(&self) -> Vec<u8> {
+        envelope::to_candid(&self.0)
+    }
+
+    fn decode_payload(_version: u8, payload: &[u8]) -> Result<Self, String> {
+        envelope::from_candid(payload).map(StableBlock)
+    }
+}
+
+impl Storable for StableBlock {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        envelope::encode(self).into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<'_, [u8]>) -> Self {
+        envelope::decode_or_trap(&bytes)
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
@@ -0,0 +1,217 @@
+//! Emergency stop for swaps, liquidity adds and pool creation.
+//! The endpoints take `swaps_open`, `liquidity_open` or `pool_creation_open` as guard,
+//! swaps also check `pool_swaps_open` for every pool of their route. Operators halt, only admins resume.
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+
+use super::stable_circuit_breaker::{Halt, StableCircuitBreaker, Subsystem};
+use crate::audit::audit_log;
+use crate::block_log::dex_block::log_admin_action;
+use crate::pool::stable_pool::StablePool;
+use crate::rbac::guards::{is_admin, is_operator};
+use crate::stable_memory::CIRCUIT_BREAKER;
+
+thread_local! {
+    // pool_id -> (window start, price at window start). Heap only, an upgrade just starts new windows
+    static PRICE_WINDOWS: RefCell<BTreeMap<u32, (u64, f64)>> = const { RefCell::new(BTreeMap::new()) };
+}
+
+pub fn get() -> StableCircuitBreaker {
+    CIRCUIT_BREAKER.with(|c| c.borrow().get().clone())
+}
+
+fn set(breaker: StableCircuitBreaker) {
+    CIRCUIT_BREAKER.with(|c| {
+        _ = c.borrow_mut().set(breaker);
+    });
+}
+
+fn check(subsystem: Subsystem) -> Result<(), String> {
+    match get().halt_of(subsystem) {
+        Some(halt) => Err(format!("❌ {:?} are halted: {}", subsystem, halt.reason)),
+        None => Ok(()),
+    }
+}
+
+pub fn swaps_open() -> Result<(), String> {
+    check(Subsystem::Swaps)
+}
+
+pub fn liquidity_open() -> Result<(), String> {
+    check(Subsystem::Liquidity)
+}
+
+pub fn pool_creation_open() -> Result<(), String> {
+    check(Subsystem::PoolCreation)
+}
+
+pub fn pool_swaps_open(pool_id: u32) -> Result<(), String> {
+    match get().halted_pools.get(&pool_id) {
+        Some(halt) => Err(format!("❌ Swaps of pool {} are halted: {}", pool_id, halt.reason)),
+        None => Ok(()),
+    }
+}
+
+fn new_halt(reason: &str, automatic: bool) -> Halt {
+    Halt {
+        reason: reason.to_string(),
+        halted_by: if automatic { ic_cdk::api::id() } else { ic_cdk::caller() },
+        ts: ic_cdk::api::time(),
+        automatic,
+    }
+}
+
+fn halt(subsystems: &[Subsystem], reason: &str, automatic: bool) -> StableCircuitBreaker {
+    let mut breaker = get();
+    let halt = new_halt(reason, automatic);
+    for subsystem in subsystems {
+        *breaker.halt_of_mut(*subsystem) = Some(halt.clone());
+    }
+    set(breaker.clone());
+
+    let detail = format!("{:?}: {}", subsystems, reason);
+    ic_cdk::println!("🛑 Circuit breaker tripped, {}", detail);
+    log_admin_action("circuit_breaker_halt", &detail);
+    breaker
+}
+
+fn halt_pool(pool_id: u32, reason: &str) {
+    let mut breaker = get();
+    breaker.halted_pools.insert(pool_id, new_halt(reason, true));
+    set(breaker);
+
+    let detail = format!("pool {}: {}", pool_id, reason);
+    ic_cdk::println!("🛑 Circuit breaker tripped, {}", detail);
+    log_admin_action("circuit_breaker_halt", &detail);
+}
+
+fn subsystems_or_all(subsystems: Vec<Subsystem>) -> Vec<Subsystem> {
+    if subsystems.is_empty() {
+        Subsystem::ALL.to_vec()
+    } else {
+        subsystems
+    }
+}
+
+/// Percent move from anchor to price
+fn price_move_pct(anchor: f64, price: f64) -> f64 {
+    ((price - anchor) / anchor).abs() * 100.0
+}
+
+/// Called after a swap updated a pool with the pool price from before the swap. A move beyond
+/// max_price_move_pct within one price window halts the swaps of that pool, the swap that moved
+/// the price is already settled
+pub fn observe_pool_price(pool: &StablePool, price_before: Option<f64>) {
+    let breaker = get();
+    if !breaker.auto_trigger {
+        return;
+    }
+    let (price_before, price) = match (price_before, pool.get_price_as_f64()) {
+        (Some(price_before), Some(price)) if price_before > 0.0 && price > 0.0 => (price_before, price),
+        _ => return,
+    };
+    let now = ic_cdk::api::time();
+    let window_nanos = breaker.price_window_secs.saturating_mul(1_000_000_000);
+
+    let move_pct = PRICE_WINDOWS.with(|w| {
+        let mut windows = w.borrow_mut();
+        // a new window is anchored at the price before this swap, so its own move counts
+        let window = windows.entry(pool.pool_id).or_insert((now, price_before));
+        if now.saturating_sub(window.0) > window_nanos {
+            *window = (now, price_before);
+        }
+        price_move_pct(window.1, price)
+    });
+
+    if move_pct > breaker.max_price_move_pct {
+        let reason = format!(
+            "pool {} price moved {:.2}% within {}s",
+            pool.pool_id, move_pct, breaker.price_window_secs
+        );
+        halt_pool(pool.pool_id, &reason);
+        audit_log::record(ic_cdk::api::id(), "circuit_breaker_trigger", reason);
+    }
+}
+
+/// Halt the given subsystems, an empty list halts all of them
+#[ic_cdk::update(guard = "is_operator")]
+fn halt_trading(subsystems: Vec<Subsystem>, reason: String) -> Result<StableCircuitBreaker, String> {
+    if reason.trim().is_empty() {
+        Err("❌ A reason is required to halt trading")?
+    }
+    Ok(halt(&subsystems_or_all(subsystems), &reason, false))
+}
+
+/// Reopen the given subsystems, an empty list reopens all of them
+#[ic_cdk::update(guard = "is_admin")]
+fn resume_trading(subsystems: Vec<Subsystem>) -> Result<StableCircuitBreaker, String> {
+    let subsystems = subsystems_or_all(subsystems);
+    let mut breaker = get();
+    for subsystem in &subsystems {
+        *breaker.halt_of_mut(*subsystem) = None;
+    }
+    set(breaker.clone());
+    // fresh windows so the price that tripped the breaker is not compared against again
+    PRICE_WINDOWS.with(|w| w.borrow_mut().clear());
+
+    log_admin_action("circuit_breaker_resume", &format!("{:?}", subsystems));
+    Ok(breaker)
+}
+
+/// Reopen the swaps of a pool halted by the price move trigger
+#[ic_cdk::update(guard = "is_admin")]
+fn resume_pool_swaps(pool_id: u32) -> Result<StableCircuitBreaker, String> {
+    let mut breaker = get();
+    if breaker.halted_pools.remove(&pool_id).is_none() {
+        Err(format!("❌ Swaps of pool {} are not halted", pool_id))?
+    }
+    set(breaker.clone());
+    PRICE_WINDOWS.with(|w| w.borrow_mut().remove(&pool_id));
+
+    log_admin_action("circuit_breaker_resume", &format!("pool {}", pool_id));
+    Ok(breaker)
+}
+
+#[ic_cdk::update(guard = "is_admin")]
+fn set_price_move_trigger(
+    auto_trigger: bool,
+    max_price_move_pct: f64,
+    price_window_secs: u64,
+) -> Result<StableCircuitBreaker, String> {
+    if !max_price_move_pct.is_finite() || max_price_move_pct <= 0.0 {
+        Err("❌ Max price move must be above 0 percent")?
+    }
+    if price_window_secs == 0 {
+        Err("❌ Price window must be at least one second")?
+    }
+    let breaker = StableCircuitBreaker {
+        auto_trigger,
+        max_price_move_pct,
+        price_window_secs,
+        ..get()
+    };
+    set(breaker.clone());
+    log_admin_action(
+        "circuit_breaker_trigger_config",
+        &format!("auto_trigger: {}, max_price_move_pct: {}, price_window_secs: {}", auto_trigger, max_price_move_pct, price_window_secs),
+    );
+    Ok(breaker)
+}
+
+#[ic_cdk::query]
+fn get_circuit_breaker_status() -> StableCircuitBreaker {
+    get()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn price_move_is_symmetric_in_percent() {
+        assert_eq!(price_move_pct(2.0, 3.0), 50.0);
+        assert_eq!(price_move_pct(2.0, 1.0), 50.0);
+        assert_eq!(price_move_pct(2.0, 2.0), 0.0);
+        assert_eq!(subsystems_or_all(vec![]).len(), 3);
+    }
+}
@@ -0,0 +1,2 @@
+pub mod stable_circuit_breaker;
+pub mod breaker;
@@ -0,0 +1,77 @@
+use std::collections::BTreeMap;
+
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+use crate::stable_schema::envelope::impl_versioned_cbor;
+
+pub const DEFAULT_MAX_PRICE_MOVE_PCT: f64 = 25.0;
+pub const DEFAULT_PRICE_WINDOW_SECS: u64 = 60;
+
+#[derive(CandidType, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Subsystem {
+    Swaps,
+    Liquidity, // adding liquidity, removals always stay open so LPs can exit
+    PoolCreation,
+}
+
+impl Subsystem {
+    pub const ALL: [Subsystem; 3] = [Subsystem::Swaps, Subsystem::Liquidity, Subsystem::PoolCreation];
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct Halt {
+    pub reason: String,
+    pub halted_by: Principal, // the canister itself for automatic triggers
+    pub ts: u64,
+    pub automatic: bool,
+}
+
+/// Per subsystem halts, per pool swap halts and the price move trigger, None means the subsystem is open
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableCircuitBreaker {
+    #[serde(default)]
+    pub swaps: Option<Halt>,
+    #[serde(default)]
+    pub liquidity: Option<Halt>,
+    #[serde(default)]
+    pub pool_creation: Option<Halt>,
+    #[serde(default)]
+    pub halted_pools: BTreeMap<u32, Halt>, // pool_id -> halt of the swaps through that pool only
+    pub auto_trigger: bool,
+    pub max_price_move_pct: f64, // halt a pool's swaps when its price moves more than this within price_window_secs
+    pub price_window_secs: u64,
+}
+
+impl Default for StableCircuitBreaker {
+    fn default() -> Self {
+        Self {
+            swaps: None,
+            liquidity: None,
+            pool_creation: None,
+            halted_pools: BTreeMap::new(),
+            auto_trigger: false,
+            max_price_move_pct: DEFAULT_MAX_PRICE_MOVE_PCT,
+            price_window_secs: DEFAULT_PRICE_WINDOW_SECS,
+        }
+    }
+}
+
+impl StableCircuitBreaker {
+    pub fn halt_of(&self, subsystem: Subsystem) -> &Option<Halt> {
+        match subsystem {
+            Subsystem::Swaps => &self.swaps,
+            Subsystem::Liquidity => &self.liquidity,
+            Subsystem::PoolCreation => &self.pool_creation,
+        }
+    }
+
+    pub fn halt_of_mut(&mut self, subsystem: Subsystem) -> &mut Option<Halt> {
+        match subsystem {
+            Subsystem::Swaps => &mut self.swaps,
+            Subsystem::Liquidity => &mut self.liquidity,
+            Subsystem::PoolCreation => &mut self.pool_creation,
+        }
+    }
+}
+
+impl_versioned_cbor!(StableCircuitBreaker);
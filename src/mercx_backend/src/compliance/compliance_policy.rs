@@ -0,0 +1,45 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+/// What a principal needs before it may use an operation, ordered from least to most strict
+#[derive(CandidType, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum ComplianceLevel {
+    Open,
+    Whitelisted, // in WHITELIST, which the KYC canister fills on verification
+    KycVerified, // kyc_status of the KYC canister
+}
+
+#[derive(CandidType, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Operation {
+    Swap,
+    AddLiquidity,
+    AddPool,
+}
+
+/// Level per operation. Liquidity removals and withdrawals are never gated so holders can always exit
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct CompliancePolicy {
+    pub swaps: ComplianceLevel,
+    pub liquidity: ComplianceLevel,
+    pub pool_creation: ComplianceLevel,
+}
+
+impl Default for CompliancePolicy {
+    fn default() -> Self {
+        Self {
+            swaps: ComplianceLevel::Whitelisted,
+            liquidity: ComplianceLevel::Whitelisted,
+            pool_creation: ComplianceLevel::Whitelisted,
+        }
+    }
+}
+
+impl CompliancePolicy {
+    pub fn level_for(&self, operation: Operation) -> ComplianceLevel {
+        match operation {
+            Operation::Swap => self.swaps,
+            Operation::AddLiquidity => self.liquidity,
+            Operation::AddPool => self.pool_creation,
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod compliance_policy;
+pub mod policy;
+pub mod rules;
+pub mod token_rules;
@@ -0,0 +1,72 @@
+use candid::Principal;
+
+use super::compliance_policy::{ComplianceLevel, CompliancePolicy, Operation};
+use crate::ic::address::Address;
+use crate::ic::general::is_whitelisted;
+use crate::kyc::kyc_id::get_kyc_status;
+use crate::rbac::guards::is_compliance;
+use crate::stable_mercx_settings::handlers::record_change;
+use crate::stable_mercx_settings::mercx_settings_map;
+use crate::StableToken;
+
+/// Strictest of the operation's level and the levels of the tokens it touches
+fn required_level(operation: Operation, tokens: &[&StableToken]) -> ComplianceLevel {
+    let policy_level = mercx_settings_map::get().compliance_policy.level_for(operation);
+    tokens
+        .iter()
+        .filter_map(|token| token.compliance_level)
+        .fold(policy_level, ComplianceLevel::max)
+}
+
+async fn check_principal(principal: Principal, level: ComplianceLevel) -> Result<(), String> {
+    match level {
+        ComplianceLevel::Open => Ok(()),
+        ComplianceLevel::Whitelisted => {
+            if !is_whitelisted(principal) {
+                Err(format!("❌ {} is not whitelisted, complete KYC first", principal))?
+            }
+            Ok(())
+        }
+        ComplianceLevel::KycVerified => {
+            if !get_kyc_status(principal).await? {
+                Err(format!("❌ {} is not KYC verified", principal))?
+            }
+            Ok(())
+        }
+    }
+}
+
+/// Check the caller, and the recipient when tokens go to a receive_address, against the policy
+pub async fn check(
+    operation: Operation,
+    caller: Principal,
+    tokens: &[&StableToken],
+    recipient: Option<&Address>,
+) -> Result<(), String> {
+    let level = required_level(operation, tokens);
+    check_principal(caller, level).await?;
+
+    match recipient {
+        Some(Address::PrincipalId(account)) if account.owner != caller => check_principal(account.owner, level)
+            .await
+            .map_err(|e| format!("{} (receive address)", e)),
+        // an account id does not reveal its owner, so it can not be checked
+        Some(Address::AccountId(_)) if level != ComplianceLevel::Open => {
+            Err("❌ Receive address must be a principal account for this token".to_string())
+        }
+        _ => Ok(()),
+    }
+}
+
+#[ic_cdk::query]
+fn get_compliance_policy() -> CompliancePolicy {
+    mercx_settings_map::get().compliance_policy
+}
+
+#[ic_cdk::update(guard = "is_compliance")]
+fn set_compliance_policy(policy: CompliancePolicy) -> CompliancePolicy {
+    let old = mercx_settings_map::get().compliance_policy;
+    mercx_settings_map::set_compliance_policy(policy.clone());
+    record_change("compliance_policy", format!("{:?}", old), format!("{:?}", policy));
+    policy
+}
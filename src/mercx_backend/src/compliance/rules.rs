@@ -0,0 +1,110 @@
+use candid::{Nat, Principal};
+
+use super::token_rules::TokenRules;
+use crate::balances::balance_map::get_balance;
+use crate::balances::stable_balance::StableBalanceId;
+use crate::helpers::math_helpers::{nat_add, nat_is_zero};
+use crate::ic::address::Address;
+use crate::ic::transfer::icrc1_balance_of;
+use crate::kyc::kyc_cache;
+use crate::stable_memory::TOKEN_ACQUISITIONS;
+use crate::token::handlers::get_by_token;
+use crate::StableToken;
+
+/// Rules the DEX applies before `incoming` of `token` reaches `recipient`, either by a ledger transfer
+/// or as internal balance. A zero `incoming` moves tokens the recipient already holds, so the cap is skipped
+/// Liquidity removals and withdrawals are checked too, only the lockup does not apply to them
+pub async fn check_recipient(token: &StableToken, recipient: &Address, incoming: &Nat) -> Result<(), String> {
+    let Some(rules) = &token.rules else {
+        return Ok(());
+    };
+    let account = match recipient {
+        Address::PrincipalId(account) => account,
+        // an account id does not reveal its owner, so none of the rules can be checked
+        Address::AccountId(_) => Err(format!("❌ {} can only be sent to a principal account", token.symbol))?,
+    };
+
+    if rules.kyc_only || !rules.blocked_jurisdictions.is_empty() {
+        let user = kyc_cache::get_user(account.owner).await?;
+        if rules.kyc_only && !user.as_ref().is_some_and(|user| user.kyc_status) {
+            Err(format!("❌ {} can only be sent to KYC verified principals, {} is not", token.symbol, account.owner))?
+        }
+        if !rules.blocked_jurisdictions.is_empty() {
+            match user.and_then(|user| user.jurisdiction) {
+                Some(jurisdiction) if rules.blocked_jurisdictions.contains(&jurisdiction) => Err(format!(
+                    "❌ {} can not be sent to holders in {}",
+                    token.symbol, jurisdiction
+                ))?,
+                Some(_) => {}
+                None => Err(format!(
+                    "❌ {} is restricted by jurisdiction and {} has none on record",
+                    token.symbol, account.owner
+                ))?,
+            }
+        }
+    }
+
+    if let Some(max_holding) = &rules.max_holding {
+        if !nat_is_zero(incoming) {
+            let held = nat_add(
+                &icrc1_balance_of(token, account).await?,
+                &get_balance(account.owner, token.token_id),
+            );
+            let after = nat_add(&held, incoming);
+            if after > *max_holding {
+                Err(format!(
+                    "❌ {} holder cap is {}, {} would hold {}",
+                    token.symbol, max_holding, account.owner, after
+                ))?
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// End of the lockup of `holder` for `token` if it is still running
+pub fn locked_until(token: &StableToken, holder: Principal) -> Option<u64> {
+    let lockup_secs = token.rules.as_ref()?.lockup_secs?;
+    let acquired = TOKEN_ACQUISITIONS.with(|m| m.borrow().get(&StableBalanceId(holder, token.token_id)))?;
+    let until = acquired.saturating_add(lockup_secs.saturating_mul(1_000_000_000));
+    (until > ic_cdk::api::time()).then_some(until)
+}
+
+/// Refuses to let `holder` sell or pool `token` during its lockup
+pub fn check_lockup(token: &StableToken, holder: Principal) -> Result<(), String> {
+    match locked_until(token, holder) {
+        Some(until) => Err(format!("❌ {} is locked for {} until {}", token.symbol, holder, until)),
+        None => Ok(()),
+    }
+}
+
+/// Starts the lockup of `holder` after buying `token`
+pub fn record_purchase(token: &StableToken, holder: Principal, ts: u64) {
+    if token.rules.as_ref().is_some_and(|rules| rules.lockup_secs.is_some()) {
+        TOKEN_ACQUISITIONS.with(|m| m.borrow_mut().insert(StableBalanceId(holder, token.token_id), ts));
+    }
+}
+
+/// Rules of a swap: the pay token leaves the caller, the receive token goes to `to_address`
+pub async fn check_swap(
+    caller: Principal,
+    pay_token: &StableToken,
+    receive_token: &StableToken,
+    receive_amount: &Nat,
+    to_address: &Address,
+) -> Result<(), String> {
+    check_lockup(pay_token, caller)?;
+    check_recipient(receive_token, to_address, receive_amount).await
+}
+
+#[ic_cdk::query]
+fn get_token_rules(token: String) -> Result<Option<TokenRules>, String> {
+    Ok(get_by_token(&token)?.rules)
+}
+
+/// End of the caller's lockup for a token, None when it can be sold
+#[ic_cdk::query]
+fn get_lockup_until(token: String) -> Result<Option<u64>, String> {
+    Ok(locked_until(&get_by_token(&token)?, ic_cdk::caller()))
+}
@@ -0,0 +1,69 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::math_helpers::nat_is_zero;
+
+/// Transfer restrictions of a tokenized security, checked before the DEX sends the token to anyone
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TokenRules {
+    pub max_holding: Option<Nat>,           // cap per holder, ledger balance plus internal balance
+    pub kyc_only: bool,                     // recipients must be KYC verified
+    pub blocked_jurisdictions: Vec<String>, // ISO 3166-1 alpha-2 codes recipients may not be in
+    pub lockup_secs: Option<u64>,           // a buyer can not sell or pool the token for this long after a swap
+}
+
+impl TokenRules {
+    /// Checks the rules and upper-cases the jurisdiction codes
+    pub fn validate(self) -> Result<Self, String> {
+        if self.max_holding.as_ref().is_some_and(nat_is_zero) {
+            Err("❌ max_holding must be above zero, use None for no cap")?
+        }
+        if self.lockup_secs == Some(0) {
+            Err("❌ lockup_secs must be above zero, use None for no lockup")?
+        }
+        let blocked_jurisdictions = self
+            .blocked_jurisdictions
+            .iter()
+            .map(|code| normalize_jurisdiction(code))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Self {
+            blocked_jurisdictions,
+            ..self
+        })
+    }
+}
+
+pub fn normalize_jurisdiction(code: &str) -> Result<String, String> {
+    let code = code.trim().to_uppercase();
+    if code.len() != 2 || !code.chars().all(|c| c.is_ascii_alphabetic()) {
+        Err(format!("❌ Invalid jurisdiction '{}', expected an ISO 3166-1 alpha-2 code", code))?
+    }
+    Ok(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rules() -> TokenRules {
+        TokenRules {
+            max_holding: Some(Nat::from(1_000_u32)),
+            kyc_only: true,
+            blocked_jurisdictions: vec![" us".to_string(), "Ir".to_string()],
+            lockup_secs: Some(86_400),
+        }
+    }
+
+    #[test]
+    fn validate_normalizes_jurisdictions() {
+        let rules = rules().validate().unwrap();
+        assert_eq!(rules.blocked_jurisdictions, vec!["US".to_string(), "IR".to_string()]);
+    }
+
+    #[test]
+    fn validate_rejects_bad_values() {
+        assert!(TokenRules { blocked_jurisdictions: vec!["USA".to_string()], ..rules() }.validate().is_err());
+        assert!(TokenRules { max_holding: Some(Nat::from(0_u32)), ..rules() }.validate().is_err());
+        assert!(TokenRules { lockup_secs: Some(0), ..rules() }.validate().is_err());
+    }
+}
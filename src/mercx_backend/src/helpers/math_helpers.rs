@@ -116,6 +116,23 @@ pub fn nat_to_decimals_f64(decimals: u8, amount: &Nat) -> Option<f64> {
     Some(round_f64(real_amount, decimals))
 }
 
+/// Exact decimal string of a token amount, e.g. 150000000 with 8 decimals is "1.5"
+pub fn nat_to_decimal_string(decimals: u8, amount: &Nat) -> String {
+    let digits = nat_to_biguint(amount).to_string();
+    let decimals = decimals as usize;
+    if decimals == 0 {
+        return digits;
+    }
+    let padded = format!("{:0>width$}", digits, width = decimals + 1);
+    let (whole, fraction) = padded.split_at(padded.len() - decimals);
+    let fraction = fraction.trim_end_matches('0');
+    if fraction.is_empty() {
+        whole.to_string()
+    } else {
+        format!("{}.{}", whole, fraction)
+    }
+}
+
 pub fn nat_to_biguint(n: &Nat) -> BigUint {
     BigUint::from_bytes_be(&n.0.to_bytes_be())
 }
\ No newline at end of file
@@ -0,0 +1,105 @@
+use candid::Nat;
+
+use super::history_reply::{HistoryAmount, HistoryBlock, HistoryEntry, HistoryFilter, HistoryPage, HistoryTxType};
+use crate::ic::id::caller;
+use crate::stable_tx::stable_tx::StableTx;
+use crate::stable_tx::tx_map;
+use crate::token::handlers::get_by_token_id;
+use crate::transfers::handlers::get_by_transfer_id;
+use crate::transfers::tx_id::TxId;
+
+const DEFAULT_HISTORY_LIMIT: u32 = 20;
+const MAX_HISTORY_LIMIT: u32 = 100;
+// bounds the work of one query when a filter skips most entries, the cursor resumes the scan
+const MAX_HISTORY_SCAN: usize = 1_000;
+
+/// The caller's swaps and liquidity events, newest first.
+#[ic_cdk::query]
+pub fn get_my_history(cursor: Option<u64>, limit: Option<u32>, filter: Option<HistoryFilter>) -> HistoryPage {
+    let limit = limit.unwrap_or(DEFAULT_HISTORY_LIMIT).clamp(1, MAX_HISTORY_LIMIT) as usize;
+    let filter = filter.unwrap_or_default();
+
+    // one extra id tells whether the scan stopped before the end of the user's history
+    let tx_ids = tx_map::get_tx_ids_by_user(caller(), cursor, MAX_HISTORY_SCAN + 1);
+    let mut entries = Vec::new();
+    let mut next_cursor = None;
+    for (i, tx_id) in tx_ids.iter().enumerate() {
+        if entries.len() == limit || i == MAX_HISTORY_SCAN {
+            // resume after the last entry looked at
+            next_cursor = i.checked_sub(1).map(|i| tx_ids[i]);
+            break;
+        }
+        if let Some(tx) = tx_map::get_by_tx_id(*tx_id).filter(|tx| matches(tx, &filter)) {
+            entries.push(to_history_entry(&tx));
+        }
+    }
+
+    HistoryPage { entries, next_cursor }
+}
+
+fn tx_type(tx: &StableTx) -> HistoryTxType {
+    match tx {
+        StableTx::Swap(_) => HistoryTxType::Swap,
+        StableTx::AddPool(_) => HistoryTxType::AddPool,
+        StableTx::AddLiquidity(_) => HistoryTxType::AddLiquidity,
+        StableTx::RemoveLiquidity(_) => HistoryTxType::RemoveLiquidity,
+    }
+}
+
+fn matches(tx: &StableTx, filter: &HistoryFilter) -> bool {
+    if let Some(tx_types) = &filter.tx_types {
+        if !tx_types.contains(&tx_type(tx)) {
+            return false;
+        }
+    }
+    if let Some(pool_id) = filter.pool_id {
+        if !tx.pool_ids().contains(&pool_id) {
+            return false;
+        }
+    }
+    filter.from_ts.is_none_or(|from_ts| tx.ts() >= from_ts) && filter.to_ts.is_none_or(|to_ts| tx.ts() < to_ts)
+}
+
+fn to_history_amount((token_id, amount): (u32, Nat)) -> HistoryAmount {
+    let token = get_by_token_id(token_id);
+    HistoryAmount {
+        symbol: token.as_ref().map(|token| token.symbol()).unwrap_or_default(),
+        canister_id: token.map(|token| token.canister_id.to_string()).unwrap_or_default(),
+        amount,
+    }
+}
+
+fn to_history_entry(tx: &StableTx) -> HistoryEntry {
+    let (paid, received) = tx.token_amounts();
+    let lp_token_amount = match tx {
+        StableTx::Swap(_) => None,
+        StableTx::AddPool(tx) => Some(tx.add_lp_token_amount.clone()),
+        StableTx::AddLiquidity(tx) => Some(tx.add_lp_token_amount.clone()),
+        StableTx::RemoveLiquidity(tx) => Some(tx.remove_lp_token_amount.clone()),
+    };
+    let blocks = tx
+        .transfer_ids()
+        .iter()
+        .filter_map(|transfer_id| get_by_transfer_id(*transfer_id))
+        .filter_map(|transfer| match transfer.tx_id {
+            TxId::BlockIndex(block_index) => Some(HistoryBlock {
+                symbol: get_by_token_id(transfer.token_id).map(|token| token.symbol()).unwrap_or_default(),
+                block_index,
+                is_send: transfer.is_send,
+            }),
+            TxId::TransactionHash(_) => None,
+        })
+        .collect();
+
+    HistoryEntry {
+        tx_id: tx.tx_id(),
+        tx_type: tx_type(tx),
+        pool_ids: tx.pool_ids(),
+        paid: paid.into_iter().map(to_history_amount).collect(),
+        received: received.into_iter().map(to_history_amount).collect(),
+        lp_token_amount,
+        usd_value: tx.usd_value(),
+        blocks,
+        ts: tx.ts(),
+    }
+}
@@ -0,0 +1,54 @@
+use candid::{CandidType, Nat};
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HistoryTxType {
+    Swap,
+    AddPool,
+    AddLiquidity,
+    RemoveLiquidity,
+}
+
+/// Optional restrictions for get_my_history, all set fields must match
+#[derive(CandidType, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HistoryFilter {
+    pub tx_types: Option<Vec<HistoryTxType>>,
+    pub pool_id: Option<u32>,
+    pub from_ts: Option<u64>, // inclusive
+    pub to_ts: Option<u64>,   // exclusive
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryAmount {
+    pub symbol: String,
+    pub canister_id: String,
+    pub amount: Nat,
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryBlock {
+    pub symbol: String,
+    pub block_index: Nat,
+    pub is_send: bool, // true when the user sent the tokens to the backend
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    pub tx_id: u64,
+    pub tx_type: HistoryTxType,
+    pub pool_ids: Vec<u32>,
+    pub paid: Vec<HistoryAmount>,
+    pub received: Vec<HistoryAmount>,
+    pub lp_token_amount: Option<Nat>, // LP tokens minted or burned by liquidity events
+    pub usd_value: Option<f64>,       // at execution time, None until priced
+    pub blocks: Vec<HistoryBlock>,    // ledger blocks, empty for balance settled events
+    pub ts: u64,
+}
+
+/// One page of history, newest first.
+/// Pass next_cursor back as cursor to continue, None means there are no more entries.
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryPage {
+    pub entries: Vec<HistoryEntry>,
+    pub next_cursor: Option<u64>,
+}
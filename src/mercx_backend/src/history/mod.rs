@@ -0,0 +1,2 @@
+pub mod history_reply;
+pub mod get_my_history;
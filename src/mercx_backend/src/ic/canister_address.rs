@@ -18,6 +18,26 @@ pub const CANISTER_ID_XRC: &str =  "c5kvi-uuaaa-aaaaa-qaaia-cai";
 #[cfg(feature = "prod")]
 pub const CANISTER_ID_XRC: &str = "uf6dk-hyaaa-aaaaq-qaaaq-cai";
 
+#[cfg(not(feature = "prod"))]
+pub const MERCX_LEDGER_ID: &str = "b77ix-eeaaa-aaaaa-qaada-cai";
+
+#[cfg(feature = "prod")]
+pub const MERCX_LEDGER_ID: &str = "7p6gu-biaaa-aaaap-aknta-cai";
+
+#[cfg(not(feature = "prod"))]
+pub const MERCX_INDEX_ID: &str = "bd3sg-teaaa-aaaaa-qaaba-cai";
+
+#[cfg(feature = "prod")]
+pub const MERCX_INDEX_ID: &str = "7i7aa-mqaaa-aaaap-akntq-cai";
+
+#[cfg(not(feature = "prod"))]
+pub const ICP_LEDGER_ID: &str = "br5f7-7uaaa-aaaaa-qaaca-cai";
+
+#[cfg(feature = "prod")]
+pub const ICP_LEDGER_ID: &str = "ryjl3-tyaaa-aaaaa-aaaba-cai";
+
+// the constants above are only defaults, init/upgrade args and set_canister_ids override them, see canister_ids.rs
+
 
 //cargo build --features staging
 //cargo build => mainnet
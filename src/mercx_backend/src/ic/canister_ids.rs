@@ -0,0 +1,104 @@
+use candid::{CandidType, Principal};
+use icrc_ledger_types::icrc1::account::Account;
+use serde::{Deserialize, Serialize};
+
+use super::canister_address::{CANISTER_ID_XRC, ICP_LEDGER_ID, KYC_CANISTER_ID, MERCX_INDEX_ID, MERCX_LEDGER_ID};
+use crate::rbac::guards::is_admin;
+use crate::rbac::role::Role;
+use crate::rbac::roles_map;
+use crate::stable_mercx_settings::handlers::record_change;
+use crate::stable_mercx_settings::mercx_settings_map;
+
+/// Canisters the backend talks to. None falls back to the compile-time default of canister_address.rs
+#[derive(CandidType, Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CanisterIds {
+    pub kyc_canister: Option<Principal>,
+    pub xrc_canister: Option<Principal>,
+    pub mercx_ledger: Option<Principal>,
+    pub mercx_index: Option<Principal>,
+    pub icp_ledger: Option<Principal>,
+}
+
+/// Argument of init and post_upgrade, every field is optional so `dfx deploy` without arguments keeps the stored values
+#[derive(CandidType, Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MercxArgs {
+    pub canister_ids: Option<CanisterIds>,
+}
+
+fn resolve(id: Option<Principal>, default: &str) -> Principal {
+    id.unwrap_or_else(|| Principal::from_text(default).unwrap())
+}
+
+fn stored() -> CanisterIds {
+    mercx_settings_map::get().canister_ids
+}
+
+pub fn kyc_canister() -> Principal {
+    resolve(stored().kyc_canister, KYC_CANISTER_ID)
+}
+
+pub fn xrc_canister() -> Principal {
+    resolve(stored().xrc_canister, CANISTER_ID_XRC)
+}
+
+pub fn mercx_ledger() -> Principal {
+    resolve(stored().mercx_ledger, MERCX_LEDGER_ID)
+}
+
+pub fn mercx_index() -> Principal {
+    resolve(stored().mercx_index, MERCX_INDEX_ID)
+}
+
+pub fn icp_ledger() -> Principal {
+    resolve(stored().icp_ledger, ICP_LEDGER_ID)
+}
+
+fn resolved() -> CanisterIds {
+    CanisterIds {
+        kyc_canister: Some(kyc_canister()),
+        xrc_canister: Some(xrc_canister()),
+        mercx_ledger: Some(mercx_ledger()),
+        mercx_index: Some(mercx_index()),
+        icp_ledger: Some(icp_ledger()),
+    }
+}
+
+/// Store the given ids, fields left None keep their current value.
+/// The KYC canister whitelists verified users, so it holds the Compliance role and that follows the id.
+fn update(ids: CanisterIds) {
+    let old_kyc = kyc_canister();
+    let current = stored();
+    mercx_settings_map::set_canister_ids(CanisterIds {
+        kyc_canister: ids.kyc_canister.or(current.kyc_canister),
+        xrc_canister: ids.xrc_canister.or(current.xrc_canister),
+        mercx_ledger: ids.mercx_ledger.or(current.mercx_ledger),
+        mercx_index: ids.mercx_index.or(current.mercx_index),
+        icp_ledger: ids.icp_ledger.or(current.icp_ledger),
+    });
+
+    let new_kyc = kyc_canister();
+    if new_kyc != old_kyc {
+        roles_map::remove(old_kyc, Role::Compliance);
+    }
+    roles_map::insert(new_kyc, Role::Compliance);
+}
+
+/// Apply init/upgrade args. The backend's own account is always taken from the running canister
+pub fn apply_args(args: Option<MercxArgs>) {
+    mercx_settings_map::set_mercx_backend(Account::from(ic_cdk::api::id()));
+    update(args.and_then(|args| args.canister_ids).unwrap_or_default());
+}
+
+#[ic_cdk::update(guard = "is_admin")]
+fn set_canister_ids(ids: CanisterIds) -> CanisterIds {
+    let old = resolved();
+    update(ids);
+    let new = resolved();
+    record_change("canister_ids", format!("{:?}", old), format!("{:?}", new));
+    new
+}
+
+#[ic_cdk::query]
+fn get_canister_ids() -> CanisterIds {
+    resolved()
+}
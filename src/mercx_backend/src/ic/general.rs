@@ -7,6 +7,12 @@ use icrc_ledger_types::icrc2::transfer_from::{TransferFromArgs, TransferFromErro
 use serde::Serialize;
 //use ic_cdk::caller;
 use crate::stable_memory::WHITELIST;
+use crate::block_log::dex_block::{log_admin_action, log_fee_withdraw};
+use crate::rbac::guards::{is_admin, is_compliance};
+use crate::ic::canister_ids;
+use crate::circuit_breaker::breaker::swaps_open;
+use crate::compliance::compliance_policy::Operation;
+use crate::compliance::policy as compliance;
 
 
 // pub const CANISTER_ID_XRC:&str="uf6dk-hyaaa-aaaaq-qaaaq-cai";
@@ -28,15 +34,6 @@ use crate::stable_memory::WHITELIST;
 // pub const CANISTER_ID_CKUSDT_LEDGER_CANISTER: &str = "br5f7-7uaaa-aaaaa-qaaca-cai";
 
 
-//Nour's Id
-pub const CANISTER_ID_XRC: &str = "c5kvi-uuaaa-aaaaa-qaaia-cai";
-pub const CANISTER_ID_ICRC1_LEDGER_CANISTER: &str = "b77ix-eeaaa-aaaaa-qaada-cai";
-pub const CANISTER_ID_ICRC1_INDEX_CANISTER: &str = "bw4dl-smaaa-aaaaa-qaacq-cai";
-pub const CANISTER_ID_ICP_LEDGER_CANISTER: &str = "br5f7-7uaaa-aaaaa-qaaca-cai";
-//pub const CANISTER_ID_ICP_INDEX_CANISTER: &str = "qhbym-qaaaa-aaaaa-aaafq-cai";
-pub const CANISTER_ID_MERCX_BACKEND: &str = "ahw5u-keaaa-aaaaa-qaaha-cai";
-pub const CANISTER_ID_TOMMY_LEDGER_CANISTER: &str = "by6od-j4aaa-aaaaa-qaadq-cai";
-pub const CANISTER_ID_CKUSDT_LEDGER_CANISTER: &str = "bkyz2-fmaaa-aaaaa-qaaaq-cai";
 
 
 
@@ -56,27 +53,30 @@ pub struct TransferArgs {
     to_account: Account,
 }
 
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_compliance")]
 fn add_to_whitelist(principal: Principal) {
     WHITELIST.with(|whitelist| {
         whitelist.borrow_mut().insert(principal, true);
     });
+    log_admin_action("add_to_whitelist", &principal.to_text());
     ic_cdk::println!("Added {} to whitelist", principal.to_text());
 }
 
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_compliance")]
 fn remove_from_whitelist(principal: Principal) {
     WHITELIST.with(|whitelist| {
         whitelist.borrow_mut().remove(&principal);
     });
+    log_admin_action("remove_from_whitelist", &principal.to_text());
     ic_cdk::println!("Removed {} from whitelist", principal.to_text());
 }
 
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_admin")]
 fn reset_whitelist() {
     WHITELIST.with(|whitelist| {
         whitelist.borrow_mut().clear_new();
     });
+    log_admin_action("reset_whitelist", "");
     ic_cdk::println!("✅ Whitelist has been reset.");
 }
 
@@ -93,7 +93,7 @@ fn get_whitelisted_principals() -> Vec<String> {
 
 
 #[ic_cdk::query]
-fn is_whitelisted(principle:Principal) -> bool {
+pub fn is_whitelisted(principle:Principal) -> bool {
 
     WHITELIST.with(|whitelist| {
         whitelist.borrow().get(&principle).unwrap_or(false)
@@ -101,7 +101,7 @@ fn is_whitelisted(principle:Principal) -> bool {
 }
 
 
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_admin")]
 async fn transfer(args: TransferArgs) -> Result<BlockIndex, String> {
     ic_cdk::println!(
         "Transferring {} tokens to account {}",
@@ -143,8 +143,7 @@ async fn transfer(args: TransferArgs) -> Result<BlockIndex, String> {
     ic_cdk::call::<(TransferArg,), (Result<BlockIndex, TransferError>,)>(
         // 2. Convert a textual representation of a Principal into an actual Principal object. The principal is the one we specified in dfx.json.
         //    expect will panic if the conversion fails, ensuring the code does not proceed with an invalid principal.
-        Principal::from_text(CANISTER_ID_ICRC1_LEDGER_CANISTER)
-            .expect("Could not decode the principal."),
+        canister_ids::mercx_ledger(),
         // 3. Specify the method name on the target canister to be called, in this case, "icrc1_transfer".
         "icrc1_transfer",
         // 4. Provide the arguments for the call in a tuple, here transfer_args is encapsulated as a single-element tuple.
@@ -190,8 +189,7 @@ async fn transfer_from(args: TransferArgs) -> Result<BlockIndex, String> {
     ic_cdk::call::<(TransferFromArgs,), (Result<BlockIndex, TransferFromError>,)>(
         // 2. Convert a textual representation of a Principal into an actual `Principal` object. The principal is the one we specified in `dfx.json`.
         //    `expect` will panic if the conversion fails, ensuring the code does not proceed with an invalid principal.
-        Principal::from_text(CANISTER_ID_ICP_LEDGER_CANISTER)
-            .expect("Could not decode the principal."),
+        canister_ids::icp_ledger(),
         // 3. Specify the method name on the target canister to be called, in this case, "icrc1_transfer".
         "icrc2_transfer_from",
         // 4. Provide the arguments for the call in a tuple, here `transfer_args` is encapsulated as a single-element tuple.
@@ -237,8 +235,7 @@ async fn transfer_from_general(args: TransferArgs) -> Result<BlockIndex, String>
     ic_cdk::call::<(TransferFromArgs,), (Result<BlockIndex, TransferFromError>,)>(
         // 2. Convert a textual representation of a Principal into an actual `Principal` object. The principal is the one we specified in `dfx.json`.
         //    `expect` will panic if the conversion fails, ensuring the code does not proceed with an invalid principal.
-        Principal::from_text(CANISTER_ID_ICRC1_LEDGER_CANISTER)
-            .expect("Could not decode the principal."),
+        canister_ids::mercx_ledger(),
         // 3. Specify the method name on the target canister to be called, in this case, "icrc1_transfer".
         "icrc2_transfer_from",
         // 4. Provide the arguments for the call in a tuple, here `transfer_args` is encapsulated as a single-element tuple.
@@ -278,8 +275,7 @@ async fn deposit_icp_in_canister(amount: u64) -> Result<BlockIndex, String> {
     ic_cdk::call::<(TransferFromArgs,), (Result<BlockIndex, TransferFromError>,)>(
         // 2. Convert a textual representation of a Principal into an actual `Principal` object. The principal is the one we specified in `dfx.json`.
         //    `expect` will panic if the conversion fails, ensuring the code does not proceed with an invalid principal.
-        Principal::from_text(CANISTER_ID_ICP_LEDGER_CANISTER)
-            .expect("Could not decode the principal."),
+        canister_ids::icp_ledger(),
         // 3. Specify the method name on the target canister to be called, in this case, "icrc1_transfer".
         "icrc2_transfer_from",
         // 4. Provide the arguments for the call in a tuple, here `transfer_args` is encapsulated as a single-element tuple.
@@ -324,8 +320,7 @@ async fn deposit_token(amount: u64, token_info: Principal) -> Result<BlockIndex,
 async fn check_balance_icp(account: Account) -> NumTokens {
     // Perform the call to icrc1_balance_of canister method
     let (balance_result,): (NumTokens,) = ic_cdk::call::<(Account,), (NumTokens,)>(
-        Principal::from_text(CANISTER_ID_ICP_LEDGER_CANISTER)
-            .expect("Could not decode the principal."),
+        canister_ids::icp_ledger(),
         "icrc1_balance_of",
         (account,),
     )
@@ -340,8 +335,7 @@ async fn check_balance_icp(account: Account) -> NumTokens {
 async fn check_balance_mercx(account: Account) -> NumTokens {
     // Perform the call to icrc1_balance_of canister method
     let (balance_result,): (NumTokens,) = ic_cdk::call::<(Account,), (NumTokens,)>(
-        Principal::from_text(CANISTER_ID_ICRC1_LEDGER_CANISTER)
-            .expect("Could not decode the principal."),
+        canister_ids::mercx_ledger(),
         "icrc1_balance_of",
         (account,),
     )
@@ -395,6 +389,13 @@ pub async fn get_fee(ledger: Principal) -> Result<Nat, String> {
         .map_err(|e| e.1)
 }
 
+pub async fn get_metadata(ledger: Principal) -> Result<Vec<(String, MetadataValue)>, String> {
+    ic_cdk::call::<(), (Vec<(String, MetadataValue)>,)>(ledger, "icrc1_metadata", ())
+        .await
+        .map(|(metadata,)| metadata)
+        .map_err(|e| e.1)
+}
+
 
 // #[ic_cdk::update]
 // async fn get_logo_url() -> Result<String, String> {
@@ -573,8 +574,7 @@ async fn get_transactions(
     let get_transactions_args = TransactionQueryArgs { start, length };
 
     let (response,): (TransactionResponse,) = ic_cdk::call(
-        Principal::from_text(CANISTER_ID_ICRC1_LEDGER_CANISTER)
-            .expect("Could not decode the principal."),
+        canister_ids::mercx_ledger(),
         "get_transactions",
         (get_transactions_args,),
     )
@@ -630,7 +630,7 @@ async fn get_account_transactions(
     };
 
     let (result,): (GetTransactionsResult,) = ic_cdk::call(
-        Principal::from_text("bd3sg-teaaa-aaaaa-qaaba-cai").unwrap(),
+        canister_ids::mercx_index(),
         "get_account_transactions",
         (args,), // Pass as a single argument
     )
@@ -643,15 +643,16 @@ async fn get_account_transactions(
     }
 }
 
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_admin")]
 async fn send_mercx(amount: u64) -> Result<BlockIndex, String> {
     let caller: Principal = ic_cdk::caller();
     let amount = Nat::from(amount);
+    let ledger = canister_ids::mercx_ledger();
 
     let transfer_args: TransferArg = TransferArg {
         // can be used to distinguish between transactions
         // the amount we want to transfer
-        amount,
+        amount: amount.clone(),
         // we want to transfer tokens from the default subaccount of the canister
         from_subaccount: None,
         // if not specified, the default fee for the canister is used
@@ -666,11 +667,9 @@ async fn send_mercx(amount: u64) -> Result<BlockIndex, String> {
     // let ledger_principal = env::var("CANISTER_ID_ICRC1_LEDGER_CANISTER")
     // .expect("Ledger Canister Principal ID not set in .env");
     // 1. Asynchronously call another canister function using ic_cdk::call.
-    ic_cdk::call::<(TransferArg,), (Result<BlockIndex, TransferError>,)>(
-        // 2. Convert a textual representation of a Principal into an actual Principal object. The principal is the one we specified in dfx.json.
-        //    expect will panic if the conversion fails, ensuring the code does not proceed with an invalid principal.
-        Principal::from_text(CANISTER_ID_ICRC1_LEDGER_CANISTER)
-            .expect("Could not decode the principal."),
+    let block_index = ic_cdk::call::<(TransferArg,), (Result<BlockIndex, TransferError>,)>(
+        // 2. The ledger principal is the one we specified in dfx.json, decoded above.
+        ledger,
         // 3. Specify the method name on the target canister to be called, in this case, "icrc1_transfer".
         "icrc1_transfer",
         // 4. Provide the arguments for the call in a tuple, here transfer_args is encapsulated as a single-element tuple.
@@ -684,10 +683,13 @@ async fn send_mercx(amount: u64) -> Result<BlockIndex, String> {
     // 7. Access the first element of the tuple, which is the Result<BlockIndex, TransferError>, for further processing.
     .0
     // 8. Use map_err again to transform any specific ledger transfer errors into a readable string format, facilitating error handling and debugging.
-    .map_err(|e| format!("ledger transfer mercx error {:?}", e))
+    .map_err(|e| format!("ledger transfer mercx error {:?}", e))?;
+
+    log_fee_withdraw(caller, ledger, &amount, &block_index);
+    Ok(block_index)
 }
 
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_admin")]
 async fn send_token(amount: u64, token_info: Principal) -> Result<BlockIndex, String> {
     let caller: Principal = ic_cdk::caller();
     let amount = Nat::from(amount);
@@ -695,7 +697,7 @@ async fn send_token(amount: u64, token_info: Principal) -> Result<BlockIndex, St
     let transfer_args: TransferArg = TransferArg {
         // can be used to distinguish between transactions
         // the amount we want to transfer
-        amount,
+        amount: amount.clone(),
         // we want to transfer tokens from the default subaccount of the canister
         from_subaccount: None,
         // if not specified, the default fee for the canister is used
@@ -707,7 +709,7 @@ async fn send_token(amount: u64, token_info: Principal) -> Result<BlockIndex, St
         memo: None,
     };
 
-    ic_cdk::api::call::call::<(TransferArg,), (Result<BlockIndex, TransferError>,)>(
+    let block_index = ic_cdk::api::call::call::<(TransferArg,), (Result<BlockIndex, TransferError>,)>(
         // 2. Convert a textual representation of a Principal into an actual Principal object. The principal is the one we specified in dfx.json.
         //    expect will panic if the conversion fails, ensuring the code does not proceed with an invalid principal.
         token_info,
@@ -724,7 +726,10 @@ async fn send_token(amount: u64, token_info: Principal) -> Result<BlockIndex, St
     // 7. Access the first element of the tuple, which is the Result<BlockIndex, TransferError>, for further processing.
     .0
     // 8. Use map_err again to transform any specific ledger transfer errors into a readable string format, facilitating error handling and debugging.
-    .map_err(|e| format!("ledger transfer tommy error {:?}", e))
+    .map_err(|e| format!("ledger transfer tommy error {:?}", e))?;
+
+    log_fee_withdraw(caller, token_info, &amount, &block_index);
+    Ok(block_index)
 }
 
 
@@ -774,13 +779,13 @@ async fn send_token(amount: u64, token_info: Principal) -> Result<BlockIndex, St
 //     .map_err(|e| format!("ledger transfer tommy error {:?}", e))
 // }
 
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "swaps_open")]
 pub async fn swap(amount_icp: u64, amount_mercx: u64) -> Result<String, String> {
    
     let caller = ic_cdk::caller();
+    compliance::check(Operation::Swap, caller, &[], None).await?;
 
-    let principal = Principal::from_text(CANISTER_ID_MERCX_BACKEND)
-        .map_err(|e| format!("Error parsing principal: {:?}", e))?;
+    let principal = ic_cdk::api::id();
     let account = Account::from(principal);
     let mercx_balance = check_balance_mercx(account).await;
 
@@ -811,7 +816,7 @@ pub async fn swap(amount_icp: u64, amount_mercx: u64) -> Result<String, String>
     Ok("Swapped Successfully!".to_string())
 }
 
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "swaps_open")]
 pub async fn sell(
     amount_from: u64,
     from_token: Principal,
@@ -819,18 +824,11 @@ pub async fn sell(
     to_token: Principal,
 ) -> Result<String, String> {
     let caller = ic_cdk::caller();
-    let to_principal = Principal::from_text(CANISTER_ID_MERCX_BACKEND)
-        .map_err(|e| format!("Error parsing Mercx canister principal: {:?}", e))?;
+    let to_principal = ic_cdk::api::id();
     // let from_principal = Principal::from_text(CANISTER_ID_ICRC1_LEDGER_CANISTER)
     //     .map_err(|e| format!("Error parsing ICP ledger principal: {:?}", e))?;
 
-    // Check if the recipient is whitelisted
-    if !is_whitelisted(caller){
-        return Err(format!(
-            "Transfer failed: recipient {} is not whitelisted.",
-            caller
-        ));
-    }
+    compliance::check(Operation::Swap, caller, &[], None).await?;
 
     let account_to = Account::from(to_principal);
     let account_from = Account::from(caller);
@@ -1,5 +1,6 @@
 pub mod general;
 pub mod canister_address;
+pub mod canister_ids;
 pub mod id;
 pub mod transfer;
 pub mod verify_transfer;
@@ -14,6 +14,17 @@ pub async fn icrc1_transfer(
     to_principal_id: &Account,
     token: &StableToken,
     created_at_time: Option<u64>,
+) -> Result<Nat, String> {
+    icrc1_transfer_from_subaccount(amount, None, to_principal_id, token, created_at_time).await
+}
+
+// Transfer out of one of the backend canister's subaccounts, used to sweep user deposit subaccounts
+pub async fn icrc1_transfer_from_subaccount(
+    amount: &Nat,
+    from_subaccount: Option<[u8; 32]>,
+    to_principal_id: &Account,
+    token: &StableToken,
+    created_at_time: Option<u64>,
 ) -> Result<Nat, String> {
     if nat_is_zero(amount) {
         //if amount = 0, return Ok(block_id = 0) to return success. Don't error Err as it could be put into claims
@@ -24,7 +35,7 @@ pub async fn icrc1_transfer(
     let transfer_args: TransferArg = TransferArg {
         memo: None,
         amount: amount.clone(),
-        from_subaccount: None,
+        from_subaccount,
         fee: None,
         to: *to_principal_id,
         created_at_time,
@@ -41,6 +52,49 @@ pub async fn icrc1_transfer(
     }
 }
 
+/// Why an icrc1_transfer_checked did not go through
+pub enum TransferFailure {
+    Ledger(TransferError), // the ledger refused the transfer, nothing moved
+    Call(String),          // the call was rejected, the ledger may still have executed the transfer
+}
+
+/// icrc1_transfer that keeps ledger errors apart from call rejects, for callers that must not
+/// undo their bookkeeping unless the transfer surely failed
+pub async fn icrc1_transfer_checked(
+    amount: &Nat,
+    from_subaccount: Option<[u8; 32]>,
+    to_principal_id: &Account,
+    token: &StableToken,
+    created_at_time: Option<u64>,
+) -> Result<Nat, TransferFailure> {
+    let id = *token
+        .canister_id()
+        .ok_or_else(|| TransferFailure::Call("Invalid principal id".to_string()))?;
+
+    let transfer_args: TransferArg = TransferArg {
+        memo: None,
+        amount: amount.clone(),
+        from_subaccount,
+        fee: None,
+        to: *to_principal_id,
+        created_at_time,
+    };
+
+    ic_cdk::call::<(TransferArg,), (Result<Nat, TransferError>,)>(id, "icrc1_transfer", (transfer_args,))
+        .await
+        .map_err(|e| TransferFailure::Call(e.1))?
+        .0
+        .map_err(TransferFailure::Ledger)
+}
+
+
+pub async fn icrc1_balance_of(token: &StableToken, account: &Account) -> Result<Nat, String> {
+    let id = *token.canister_id().ok_or("Invalid principal id")?;
+    ic_cdk::call::<(Account,), (Nat,)>(id, "icrc1_balance_of", (*account,))
+        .await
+        .map(|(balance,)| balance)
+        .map_err(|e| e.1)
+}
 
 // icrc2_transfer_from using principal id's where from_principal_id has issued an icrc2_approve
 
@@ -1,4 +1,7 @@
-use candid:: Nat;
+use candid::{Nat, Principal};
+use icrc_ledger_types::icrc::generic_value::ICRC3Value;
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc3::blocks::{GetBlocksRequest, GetBlocksResult, ICRC3GenericBlock};
 use icrc_ledger_types::icrc3::transactions::{GetTransactionsRequest, GetTransactionsResponse};
 use crate::StableToken;
 use ic_ledger_types::{query_blocks, AccountIdentifier, Block, GetBlocksArgs, Operation, Subaccount, Tokens};
@@ -6,32 +9,46 @@ use crate::ic::id::{caller_account_id, caller_id};
 use crate::helpers::math_helpers::nat_to_u64;
 use crate::stable_mercx_settings::mercx_settings_map;
 use crate::ic::general::get_time;
+use crate::ic::canister_ids;
 
-#[cfg(not(feature = "prod"))]
-const ICP_CANISTER_ID: &str = "avqkn-guaaa-aaaaa-qaaea-cai";  // Testnet ICP Ledger
-#[cfg(feature = "prod")]
-const ICP_CANISTER_ID: &str = "ryjl3-tyaaa-aaaaa-aaaba-cai"; // Mainnet ICP Ledger
 
 /// Verifies a transfer by checking the ledger.
 /// For ICRC3 tokens, it tries ICRC3 methods first, falling back to traditional methods.
 /// For non-ICRC3 tokens, it uses the traditional verification methods.
 /// checks if the transfer happens correctly 
 pub async fn verify_transfer(token: &StableToken, block_id: &Nat, amount: &Nat) -> Result<(), String> {
+    let min_valid_timestamp = get_time() - mercx_settings_map::get().transfer_expiry_nanosecs;
+    verify_transfer_since(token, block_id, amount, min_valid_timestamp).await
+}
+
+/// verify_transfer with an explicit expiry, blocks older than min_valid_timestamp are rejected.
+/// Used with 0 to recover deposits that are past transfer_expiry_nanosecs.
+pub async fn verify_transfer_since(token: &StableToken, block_id: &Nat, amount: &Nat, min_valid_timestamp: u64) -> Result<(), String> {
 
 
             let canister_id = *token.canister_id().ok_or("Invalid canister id")?;
           
             let mercx_settings = mercx_settings_map::get();
-            let min_valid_timestamp = get_time() - mercx_settings.transfer_expiry_nanosecs;
             let mercx_backend_account = &mercx_settings.mercx_backend;
             let caller_account = caller_id();
 
             // if ICP ledger, use query_blocks
-            if canister_id.to_text() == ICP_CANISTER_ID {
+            if canister_id == canister_ids::icp_ledger() {
                 return verify_trnasfer_with_query_blocks(token, block_id, amount, canister_id, min_valid_timestamp, mercx_backend_account)
                     .await  .map_err(|e| format!("❌ ICP verification failed: {}", e));
             }
 
+            // ICRC-3 ledgers, use icrc3_get_blocks and fall back to get_transactions if the call itself fails
+            if token.icrc3 {
+                match get_icrc3_block(canister_id, block_id).await {
+                    Ok(block) => {
+                        return verify_transfer_with_icrc3_block(block, amount, min_valid_timestamp, mercx_backend_account, caller_account)
+                            .map_err(|e| format!("❌ ICRC-3 verification failed: {}", e));
+                    }
+                    Err(e) => ic_cdk::println!("icrc3_get_blocks failed for {}, falling back to get_transactions: {}", token.symbol(), e),
+                }
+            }
+
             // otherwise, use get_transactions
              verify_transfer_with_get_transactions(
                 token,
@@ -161,4 +178,185 @@ async fn verify_trnasfer_with_query_blocks(
         }
         Err(e) => Err(e.1)?,
     }
-}
\ No newline at end of file
+}
+/// Transfer fields parsed from an ICRC-3 generic block
+#[derive(Debug, Clone, PartialEq)]
+struct Icrc3Transfer {
+    from: Account,
+    to: Account,
+    spender: Option<Account>,
+    amount: Nat,
+    timestamp: u64,
+}
+
+/// Fetch one block with icrc3_get_blocks, following the archive callback if the ledger archived it
+async fn get_icrc3_block(canister_id: Principal, block_id: &Nat) -> Result<ICRC3GenericBlock, String> {
+    let request = vec![GetBlocksRequest {
+        start: block_id.clone(),
+        length: Nat::from(1_u32),
+    }];
+    let (result,) = ic_cdk::call::<(Vec<GetBlocksRequest>,), (GetBlocksResult,)>(canister_id, "icrc3_get_blocks", (request,))
+        .await
+        .map_err(|e| e.1)?;
+
+    if let Some(block) = result.blocks.into_iter().find(|block| block.id == *block_id) {
+        return Ok(block.block);
+    }
+
+    for archived in result.archived_blocks {
+        let callback = archived.callback;
+        let (archive_result,) = ic_cdk::call::<(Vec<GetBlocksRequest>,), (GetBlocksResult,)>(
+            callback.canister_id,
+            &callback.method,
+            (archived.args,),
+        )
+        .await
+        .map_err(|e| format!("archive {} call failed: {}", callback.canister_id, e.1))?;
+        if let Some(block) = archive_result.blocks.into_iter().find(|block| block.id == *block_id) {
+            return Ok(block.block);
+        }
+    }
+
+    Err(format!("Block id {} not found", block_id))
+}
+
+fn verify_transfer_with_icrc3_block(
+    block: ICRC3GenericBlock,
+    amount: &Nat,
+    min_valid_timestamp: u64,
+    mercx_backend_account: &Account,
+    caller_account: Account,
+) -> Result<(), String> {
+    let transfer = parse_icrc3_transfer(&block)?;
+    if transfer.from != caller_account {
+        Err("Transfer from does not match caller")?
+    }
+    if transfer.to != *mercx_backend_account {
+        Err("Transfer to does not match Mercx backend")?
+    }
+    // make sure spender is None, so not an icrc2_transfer_from transaction
+    if transfer.spender.is_some() {
+        Err("Invalid transfer spender")?
+    }
+    if transfer.amount != *amount {
+        Err(format!("Invalid transfer amount: rec {:?} exp {:?}", transfer.amount, amount))?
+    }
+    if transfer.timestamp < min_valid_timestamp {
+        Err("Expired transfer timestamp")?
+    }
+
+    Ok(())
+}
+
+/// Parse a transfer out of an ICRC-3 block.
+/// Accepts the btype form ("1xfer", "2xfer") and the legacy ICRC-1 ledger form (tx.op = "xfer").
+fn parse_icrc3_transfer(block: &ICRC3Value) -> Result<Icrc3Transfer, String> {
+    let ICRC3Value::Map(block) = block else {
+        Err("Block is not a map")?
+    };
+    let ICRC3Value::Map(tx) = block.get("tx").ok_or("Block has no tx")? else {
+        Err("Block tx is not a map")?
+    };
+
+    let kind = match (block.get("btype"), tx.get("op")) {
+        (Some(ICRC3Value::Text(btype)), _) => btype.clone(),
+        (None, Some(ICRC3Value::Text(op))) => op.clone(),
+        _ => Err("Block has no btype or tx.op")?,
+    };
+    if kind != "1xfer" && kind != "2xfer" && kind != "xfer" {
+        Err(format!("Invalid transaction kind: {}", kind))?
+    }
+
+    let from = icrc3_account(tx.get("from").ok_or("Transfer has no from")?)?;
+    let to = icrc3_account(tx.get("to").ok_or("Transfer has no to")?)?;
+    let spender = tx.get("spender").map(icrc3_account).transpose()?;
+    let amount = match tx.get("amt") {
+        Some(ICRC3Value::Nat(amount)) => amount.clone(),
+        _ => Err("Transfer has no amt")?,
+    };
+    let timestamp = match block.get("ts") {
+        Some(ICRC3Value::Nat(ts)) => nat_to_u64(ts).ok_or("Invalid block ts")?,
+        _ => Err("Block has no ts")?,
+    };
+
+    Ok(Icrc3Transfer {
+        from,
+        to,
+        spender,
+        amount,
+        timestamp,
+    })
+}
+
+/// ICRC-3 accounts are an array of [owner blob] or [owner blob, subaccount blob]
+fn icrc3_account(value: &ICRC3Value) -> Result<Account, String> {
+    let ICRC3Value::Array(parts) = value else {
+        Err("Account is not an array")?
+    };
+    let owner = match parts.first() {
+        Some(ICRC3Value::Blob(owner)) => Principal::try_from_slice(owner).map_err(|e| format!("Invalid account owner: {}", e))?,
+        _ => Err("Account has no owner")?,
+    };
+    let subaccount = match parts.get(1) {
+        Some(ICRC3Value::Blob(subaccount)) => {
+            Some(<[u8; 32]>::try_from(subaccount.as_slice()).map_err(|_| "Invalid account subaccount")?)
+        }
+        Some(_) => Err("Invalid account subaccount")?,
+        None => None,
+    };
+
+    Ok(Account { owner, subaccount })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn account_value(owner: Principal, subaccount: Option<[u8; 32]>) -> ICRC3Value {
+        let mut parts = vec![ICRC3Value::Blob(owner.as_slice().to_vec().into())];
+        if let Some(subaccount) = subaccount {
+            parts.push(ICRC3Value::Blob(subaccount.to_vec().into()));
+        }
+        ICRC3Value::Array(parts)
+    }
+
+    fn transfer_block(btype: Option<&str>, op: Option<&str>) -> ICRC3Value {
+        let mut tx = BTreeMap::new();
+        tx.insert("from".to_string(), account_value(Principal::anonymous(), None));
+        tx.insert("to".to_string(), account_value(Principal::management_canister(), Some([1; 32])));
+        tx.insert("amt".to_string(), ICRC3Value::Nat(Nat::from(1_000_u32)));
+        if let Some(op) = op {
+            tx.insert("op".to_string(), ICRC3Value::Text(op.to_string()));
+        }
+        let mut block = BTreeMap::new();
+        block.insert("ts".to_string(), ICRC3Value::Nat(Nat::from(42_u32)));
+        block.insert("tx".to_string(), ICRC3Value::Map(tx));
+        if let Some(btype) = btype {
+            block.insert("btype".to_string(), ICRC3Value::Text(btype.to_string()));
+        }
+        ICRC3Value::Map(block)
+    }
+
+    #[test]
+    fn parses_legacy_and_btype_transfer_blocks() {
+        let expected = Icrc3Transfer {
+            from: Account::from(Principal::anonymous()),
+            to: Account {
+                owner: Principal::management_canister(),
+                subaccount: Some([1; 32]),
+            },
+            spender: None,
+            amount: Nat::from(1_000_u32),
+            timestamp: 42,
+        };
+        assert_eq!(parse_icrc3_transfer(&transfer_block(None, Some("xfer"))), Ok(expected.clone()));
+        assert_eq!(parse_icrc3_transfer(&transfer_block(Some("1xfer"), None)), Ok(expected));
+    }
+
+    #[test]
+    fn rejects_non_transfer_blocks() {
+        assert!(parse_icrc3_transfer(&transfer_block(None, Some("approve"))).is_err());
+        assert!(parse_icrc3_transfer(&transfer_block(Some("1mint"), None)).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use candid::Principal;
+
+use super::kyc_id::fetch_user;
+use super::stable_kyc_user::StableKycUser;
+use crate::block_log::dex_block::log_admin_action;
+use crate::compliance::token_rules::normalize_jurisdiction;
+use crate::rbac::guards::is_compliance;
+use crate::stable_memory::KYC_CACHE;
+
+pub const KYC_CACHE_TTL_NANOS: u64 = 86_400_000_000_000; // 1 day
+
+pub fn get(principal: Principal) -> Option<StableKycUser> {
+    KYC_CACHE.with(|m| m.borrow().get(&principal))
+}
+
+fn insert(principal: Principal, user_id: u32, kyc_status: bool) -> StableKycUser {
+    let user = StableKycUser {
+        principal,
+        user_id,
+        kyc_status,
+        updated_at: ic_cdk::api::time(),
+        jurisdiction: get(principal).and_then(|user| user.jurisdiction),
+    };
+    KYC_CACHE.with(|m| m.borrow_mut().insert(principal, user.clone()));
+    user
+}
+
+/// Cached user, refreshed from the KYC canister once older than the TTL.
+/// If the KYC canister can not be reached the stale entry is used, so it is not needed on every call
+pub async fn get_user(principal: Principal) -> Result<Option<StableKycUser>, String> {
+    let cached = get(principal);
+    if let Some(user) = &cached {
+        if ic_cdk::api::time().saturating_sub(user.updated_at) < KYC_CACHE_TTL_NANOS {
+            return Ok(cached);
+        }
+    }
+
+    match fetch_user(principal).await {
+        Ok(Some(user)) => Ok(Some(insert(principal, user.user_id, user.kyc_status))),
+        Ok(None) => {
+            KYC_CACHE.with(|m| m.borrow_mut().remove(&principal));
+            Ok(None)
+        }
+        Err(e) => match cached {
+            Some(user) => {
+                ic_cdk::println!("⚠️ KYC refresh of {} failed, using cached entry: {}", principal, e);
+                Ok(Some(user))
+            }
+            None => Err(e),
+        },
+    }
+}
+
+/// Called by the KYC canister on signup and verification
+#[ic_cdk::update(guard = "is_compliance")]
+fn push_kyc_user(principal: Principal, user_id: u32, kyc_status: bool) -> StableKycUser {
+    insert(principal, user_id, kyc_status)
+}
+
+/// Called by the KYC canister when a user is deleted
+#[ic_cdk::update(guard = "is_compliance")]
+fn remove_kyc_user(principal: Principal) -> Option<StableKycUser> {
+    KYC_CACHE.with(|m| m.borrow_mut().remove(&principal))
+}
+
+/// Jurisdiction checked by the token rules, the KYC canister does not keep one
+#[ic_cdk::update(guard = "is_compliance")]
+async fn set_kyc_jurisdiction(principal: Principal, jurisdiction: Option<String>) -> Result<StableKycUser, String> {
+    let jurisdiction = jurisdiction.as_deref().map(normalize_jurisdiction).transpose()?;
+    // read after the await so a concurrent push is kept
+    get_user(principal).await?.ok_or("❌ User not found.")?;
+    let user = StableKycUser {
+        jurisdiction: jurisdiction.clone(),
+        ..get(principal).ok_or("❌ User not found.")?
+    };
+    KYC_CACHE.with(|m| m.borrow_mut().insert(principal, user.clone()));
+    log_admin_action("set_kyc_jurisdiction", &format!("{} {:?}", principal, jurisdiction));
+    Ok(user)
+}
+
+#[ic_cdk::query(guard = "is_compliance")]
+fn get_kyc_user(principal: Principal) -> Option<StableKycUser> {
+    get(principal)
+}
@@ -2,7 +2,9 @@ use ic_cdk::call;
 use crate::ic::id::caller;
 use candid::{CandidType,Principal};
 use serde::{Deserialize, Serialize};
-use crate::ic::canister_address::KYC_CANISTER_ID;
+use crate::ic::canister_ids;
+use super::kyc_cache;
+use super::stable_kyc_user::StableKycUser;
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
 pub struct User {
@@ -22,16 +24,17 @@ pub struct User {
     pub updated_at: u64,
 }
 
-// Replace `your_kyc_canister_id` with actual canister ID or pass it dynamically
-//const KYC_CANISTER_ID: &str = "ajuq4-ruaaa-aaaaa-qaaga-cai"; // example
-#[ic_cdk::update]
-pub async fn get_user_by_caller() -> Result<Option<User>, String> {
-    let caller_principal = caller();
+/// KYC user of the caller, served from the local cache
+pub async fn get_user_by_caller() -> Result<Option<StableKycUser>, String> {
+    kyc_cache::get_user(caller()).await
+}
 
+/// User as stored by the KYC canister, only the cache should need this call
+pub async fn fetch_user(principal: Principal) -> Result<Option<User>, String> {
     let (result,): (Result<Option<User>, String>,) = call(
-        Principal::from_text(KYC_CANISTER_ID).unwrap(),
+        canister_ids::kyc_canister(),
         "get_user_by_principal",
-        (caller_principal,),
+        (principal,),
     )
     .await
     .map_err(|e| format!("Call failed: {:?}", e))?; // fixed formatting
@@ -39,3 +42,7 @@ pub async fn get_user_by_caller() -> Result<Option<User>, String> {
     result
 }
 
+/// kyc_status of a principal, false when it never signed up
+pub async fn get_kyc_status(principal: Principal) -> Result<bool, String> {
+    Ok(kyc_cache::get_user(principal).await?.is_some_and(|user| user.kyc_status))
+}
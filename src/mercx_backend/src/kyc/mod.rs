@@ -1 +1,3 @@
-pub mod kyc_id;
\ No newline at end of file
+pub mod kyc_id;
+pub mod kyc_cache;
+pub mod stable_kyc_user;
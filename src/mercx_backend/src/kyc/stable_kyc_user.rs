@@ -0,0 +1,16 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+use crate::stable_schema::envelope::impl_versioned_cbor;
+
+/// Local copy of a KYC canister user, pushed by the KYC canister and refreshed after KYC_CACHE_TTL_NANOS
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StableKycUser {
+    pub principal: Principal,
+    pub user_id: u32,
+    pub kyc_status: bool,
+    pub updated_at: u64,
+    #[serde(default)]
+    pub jurisdiction: Option<String>, // ISO 3166-1 alpha-2, set by compliance, kept across refreshes
+}
+
+impl_versioned_cbor!(StableKycUser);
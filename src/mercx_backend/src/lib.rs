@@ -1,6 +1,7 @@
 pub mod ic;
 mod stable_memory;
 use crate::ic::general::{GetTransactions, TransactionResponse, TransferArgs};
+use crate::ic::canister_ids::{apply_args, CanisterIds, MercxArgs};
 pub mod xrc_mock;
 pub use xrc_mock::get_icp_rate;
 pub mod pool;
@@ -9,7 +10,11 @@ use crate::pool::add_pool_reply::AddPoolReply;
 use crate::pool::stable_pool::StablePool;
 
 pub mod token;
-use crate::token::stable_token::StableToken;
+use crate::token::stable_token::{StableToken, TokenStatus};
+use crate::token::consistency::TokenConsistencyReport;
+use crate::token::metadata_refresh::{start_token_refresh_timer, TokenRefreshReport};
+use crate::token::handlers::rebuild_token_indexes;
+use crate::transfers::handlers::rebuild_transfer_indexes;
 
 pub mod swap;
 use crate::swap::swap_reply::SwapReply;
@@ -32,7 +37,7 @@ use crate::stable_lp_token::stable_lp_token::StableLPToken;
 use crate::lp_metadata::stable_lp_metadata::LPToken;
 
 pub mod kyc;
-use crate::kyc::kyc_id::User;
+use crate::kyc::stable_kyc_user::StableKycUser;
 
 pub mod remove_liquidity;
 use crate::remove_liquidity::remove_liquidity_args::RemoveLiquidityArgs;
@@ -47,11 +52,78 @@ use crate::pool_analytics::analytics::{PoolTVL,PoolVolume};
 use crate::pool_analytics::analytics::{PoolMetrics,ProtocolStats};
 use crate::pool_analytics::stable_analytics::PoolSnapshot;
 
+pub mod balances;
+use crate::balances::balance_reply::UserBalanceReply;
+
+pub mod stable_tx;
+use crate::stable_tx::tx_map::TxPage;
+
+pub mod history;
+use crate::history::history_reply::{HistoryFilter, HistoryPage};
+
+pub mod block_log;
+use crate::block_log::block_map::certify_tip;
+use icrc_ledger_types::icrc3::archive::{GetArchivesArgs, ICRC3ArchiveInfo};
+use icrc_ledger_types::icrc3::blocks::{GetBlocksRequest, GetBlocksResult, ICRC3DataCertificate, SupportedBlockType};
+
+pub mod archive;
+use crate::archive::archive_reply::{ArchiveReport, ArchiveStats};
+use crate::archive::handlers::start_archive_timer;
+
+pub mod accounting;
+use crate::accounting::export_args::ExportArgs;
+use crate::accounting::export_reply::ExportChunk;
+
+pub mod rbac;
+use crate::rbac::role::Role;
+use crate::rbac::roles_map::bootstrap_roles;
+
+pub mod audit;
+use crate::audit::audit_log::AuditPage;
+
+pub mod stable_schema;
+use crate::stable_schema::migrations::run_migrations;
+use crate::stable_schema::schema_reply::SchemaReport;
+
+pub mod circuit_breaker;
+use crate::circuit_breaker::stable_circuit_breaker::{StableCircuitBreaker, Subsystem};
+
+pub mod compliance;
+use crate::compliance::compliance_policy::{ComplianceLevel, CompliancePolicy};
+use crate::compliance::token_rules::TokenRules;
+
+pub mod trading_hours;
+use crate::trading_hours::stable_trading_schedule::StableTradingSchedule;
+use crate::trading_hours::trading_hours_reply::TradingHoursReply;
+
 pub mod helpers;
 pub mod stable_mercx_settings;
+use crate::stable_mercx_settings::settings_reply::SettingsReply;
 use candid::Nat;
 use candid::Principal;
 use icrc_ledger_types::icrc1::account::Account;
 use icrc_ledger_types::icrc1::transfer::BlockIndex;
 use icrc_ledger_types::icrc1::transfer::NumTokens;
+
+#[ic_cdk::init]
+fn init(args: Option<MercxArgs>) {
+    bootstrap_roles("init");
+    apply_args(args);
+    start_token_refresh_timer();
+    start_archive_timer();
+}
+
+// timers are cleared on upgrade, indexes are backfilled for tokens stored before they existed
+#[ic_cdk::post_upgrade]
+fn post_upgrade(args: Option<MercxArgs>) {
+    bootstrap_roles("post_upgrade");
+    apply_args(args);
+    rebuild_token_indexes();
+    rebuild_transfer_indexes();
+    run_migrations();
+    certify_tip();
+    start_token_refresh_timer();
+    start_archive_timer();
+}
+
 ic_cdk::export_candid!();
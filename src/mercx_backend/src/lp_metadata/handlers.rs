@@ -4,6 +4,8 @@ use crate::stable_memory::LPMETADATA;
 use crate::stable_mercx_settings::mercx_settings_map;
 use crate::StableToken;
 use crate::stable_mercx_settings::mercx_settings_map::reset_lp_metadata_map_idx;
+use crate::rbac::guards::is_admin;
+use crate::block_log::dex_block::log_admin_action;
 
 pub fn get_by_token_id(token_id: u32) -> Option<LPToken> {
     LPMETADATA.with(|m| m.borrow().get(&StableLpMetadataId(token_id)))
@@ -87,13 +89,14 @@ pub fn exists(symbol: &str) -> bool {
 
 
 #[cfg(not(feature = "prod"))]
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_admin")]
 fn reset_lp_metadata_tokens() -> Result<String, String> {
     LPMETADATA.with(|tokens| {
         tokens.borrow_mut().clear_new(); // `clear_new()` btmsh kolo remove law hanmsh haga specific
     });
 
     reset_lp_metadata_map_idx();
+    log_admin_action("reset_lp_metadata_tokens", "");
 
     Ok("✅ Tokens memory cleared".to_string())
 }
@@ -121,6 +124,13 @@ mod tests {
                 icrc1: true,
                 icrc2: false,
                 icrc3: false,
+                status: None,
+                logo: None,
+                description: None,
+                metadata: None,
+                logo_overridden: None,
+                compliance_level: None,
+                rules: None,
             }
         }
     }
@@ -7,6 +7,7 @@ use crate::pool::handlers;
 use crate::StableToken;
 use crate::StablePool;
 use crate::token::stable_token::symbol;
+use crate::stable_schema::envelope::impl_versioned_cbor;
 
 
 #[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -64,14 +65,4 @@ impl LPToken {
 }
 
 
-impl Storable for LPToken {
-    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
-        serde_cbor::to_vec(self).unwrap().into()
-    }
-
-    fn from_bytes(bytes: std::borrow::Cow<'_, [u8]>) -> Self {
-        serde_cbor::from_slice(&bytes).unwrap()
-    }
-
-    const BOUND: Bound = Bound::Unbounded;
-}
\ No newline at end of file
+impl_versioned_cbor!(LPToken);
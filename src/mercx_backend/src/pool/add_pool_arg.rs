@@ -15,7 +15,7 @@ use crate::pool_analytics::analytics_storage::record_pool_snapshot2;
 use crate::stable_lp_token::lp_token_map;
 use crate::stable_mercx_settings::mercx_settings_map;
 use crate::token::add_token::add_token;
-use crate::token::handlers::get_by_token;
+use crate::token::handlers::{get_by_token, parse_canister_id};
 use crate::token::stable_token;
 use crate::transfers::handlers as transfer_handlers;
 use crate::transfers::stable_transfer::{StableTransfer, TransferType};
@@ -26,12 +26,17 @@ use crate::StableToken;
 use candid::{CandidType, Nat, Principal};
 use icrc_ledger_types::icrc1::account::Account;
 use serde::{Deserialize, Serialize};
+use crate::stable_tx::{add_pool_tx::AddPoolTx, stable_tx::StableTx, tx_map};
+use crate::circuit_breaker::breaker::pool_creation_open;
+use crate::compliance::compliance_policy::Operation;
+use crate::compliance::policy as compliance;
+use crate::compliance::rules;
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
 pub struct AddPoolArgs {
-    pub token_0: String, // e.g. FXMX
+    pub token_0: String, // e.g. FXMX or IC.<canister_id>, unlisted tokens are added by canister id
     pub amount_0: Nat,   // amount to deposit of token 0
     pub tx_id_0: Option<TxId>,
-    pub token_1: String, // e.g. ckUSDT
+    pub token_1: String, // e.g. ckUSDT or IC.<canister_id>
     pub amount_1: Nat,   // amount to deposit of token 1
     pub tx_id_1: Option<TxId>,
     pub lp_fee_bps: Option<u8>, // optional fee in basis points, default = 30 //for each swap
@@ -125,6 +130,7 @@ async fn process_add_pool(
             ts,
         )
         .await;
+        transfer_handlers::link(&transfer_ids, &[]);
         if transfer_0.is_err() {
             return Err(format!("failed. {}", transfer_0.unwrap_err()));
         } else {
@@ -149,6 +155,7 @@ async fn process_add_pool(
                 ts,
             )
             .await;
+            transfer_handlers::link(&transfer_ids, &[]);
             Err(format!("failed. {}", e))?
         }
     };
@@ -175,6 +182,7 @@ async fn process_add_pool(
                 ts,
             )
             .await;
+            transfer_handlers::link(&transfer_ids, &[]);
             return Ok(to_add_pool_reply_failed(
                 &token_0
                     .canister_id()
@@ -193,6 +201,21 @@ async fn process_add_pool(
 
     // update pool with new balances
     update_liquidity_pool(user_id, &pool, amount_0, amount_1, add_lp_token_amount, ts).await;
+    transfer_handlers::link(&transfer_ids, &[pool.pool_id]);
+    tx_map::record(&StableTx::AddPool(AddPoolTx {
+        tx_id: 0,
+        user: caller_id.owner,
+        pool_id: pool.pool_id,
+        token_id_0: token_0.token_id(),
+        amount_0: amount_0.clone(),
+        token_id_1: token_1.token_id(),
+        amount_1: amount_1.clone(),
+        add_lp_token_amount: add_lp_token_amount.clone(),
+        lp_fee_bps,
+        transfer_ids: transfer_ids.clone(),
+        ts,
+        usd_value: None,
+    }));
 
     // TODO: Return actual AddPoolReply here, depending on your logic
     Ok(to_add_pool_reply(
@@ -325,8 +348,7 @@ async fn check_arguments(
     let token_0 = match get_by_token(&args.token_0) {
         Ok(token) => token, // token_0 exists already
         Err(_) => {
-            let principal: Principal = Principal::from_text(&args.token_0)
-                .map_err(|e| format!("Invalid canister id '{}': {}", args.token_0, e))?;
+            let principal = parse_canister_id(&args.token_0)?;
             add_token(principal).await?
         }
     };
@@ -334,11 +356,24 @@ async fn check_arguments(
     let token_1 = match get_by_token(&args.token_1) {
         Ok(token) => token, // token_0 exists already
         Err(_) => {
-            let principal: Principal = Principal::from_text(&args.token_1)
-                .map_err(|e| format!("Invalid canister id '{}': {}", args.token_1, e))?;
+            let principal = parse_canister_id(&args.token_1)?;
             add_token(principal).await?
         }
     };
+    compliance::check(Operation::AddPool, ic_cdk::caller(), &[&token_0, &token_1], None).await?;
+    rules::check_lockup(&token_0, ic_cdk::caller())?;
+    rules::check_lockup(&token_1, ic_cdk::caller())?;
+
+    // delisted or deprecated tokens cannot be used for new pools
+    for token in [&token_0, &token_1] {
+        if !token.is_active() {
+            Err(format!(
+                "Token {} is {:?} and cannot be used in a new pool",
+                token.symbol(),
+                token.status()
+            ))?
+        }
+    }
 
     // make sure LP token does not already exist
     let lp_token_symbol = stable_token::symbol(&token_0, &token_1);
@@ -401,7 +436,7 @@ pub fn calculate_amounts(
     Ok((amount_0.clone(), amount_1.clone(), add_lp_token_amount))
 }
 
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "pool_creation_open")]
 pub async fn add_pool(args: AddPoolArgs) -> Result<AddPoolReply, String> {
     let (
         user_id,
@@ -465,6 +500,10 @@ pub async fn transfer_from_token(
                 tx_id: TxId::BlockIndex(block_id),
                 transfer_type: TransferType::LiquidityAdd,
                 ts,
+                account: Some(*from_principal_id),
+                pool_ids: Vec::new(),
+                request_id: None,
+                usd_price: None,
             });
             transfer_ids.push(transfer_id);
             ic_cdk::println!("💬 Transfer_0 result: {:?}", transfer_ids);
@@ -500,6 +539,10 @@ async fn verify_transfer_token(
                 tx_id: TxId::BlockIndex(tx_id.clone()),
                 transfer_type: TransferType::LiquidityAdd,
                 ts,
+                account: Some(caller_id()),
+                pool_ids: Vec::new(),
+                request_id: None,
+                usd_price: None,
             });
             transfer_ids.push(transfer_id);
 
@@ -557,6 +600,10 @@ pub async fn return_token(
                 tx_id: TxId::BlockIndex(block_id),
                 transfer_type: TransferType::Transfer,
                 ts,
+                account: Some(*to_principal_id),
+                pool_ids: Vec::new(),
+                request_id: None,
+                usd_price: None,
             });
             transfer_ids.push(transfer_id);
         }
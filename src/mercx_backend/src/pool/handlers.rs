@@ -1,5 +1,6 @@
 use crate::pool::stable_pool::{StablePool,StablePoolId};
-use crate::stable_memory::POOLS;
+use crate::block_log::dex_block::log_admin_action;
+use crate::stable_memory::{POOLS, POOL_SCHEDULES};
 use crate::pool::add_pool_reply::AddPoolReply;
 use crate::StableToken;
 use crate::token::handlers;
@@ -12,6 +13,7 @@ use crate::helpers::math_helpers::nat_zero;
 use candid::Nat;
 use candid::Principal;
 use crate::stable_lp_token::lp_token_map::{get_by_token_id_by_principal};
+use crate::rbac::guards::is_admin;
 //use std::borrow::Cow;
 pub fn symbol(token_0: &StableToken, token_1: &StableToken) -> String {
     format!("{}_{}", token_0.symbol(), token_1.symbol())
@@ -135,12 +137,14 @@ fn get_pool_price(token_0: String, token_1: String) -> Result<f64, String> {
 }
 
 
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_admin")]
 fn delete_pool(pool_id: u32) -> Result<String, String> {
     POOLS.with(|pools| {
         let mut pools = pools.borrow_mut();
 
         if pools.remove(&StablePoolId(pool_id)).is_some() {
+            POOL_SCHEDULES.with(|m| m.borrow_mut().remove(&StablePoolId(pool_id)));
+            log_admin_action("delete_pool", &pool_id.to_string());
             Ok(format!("Pool with id {} has been permanently deleted.", pool_id))
         } else {
             Err(format!("Pool with id {} not found.", pool_id))
@@ -150,13 +154,15 @@ fn delete_pool(pool_id: u32) -> Result<String, String> {
 
 //cargo build --release --features prod
 #[cfg(not(feature = "prod"))]
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_admin")]
 fn reset_pools() -> Result<String, String> {
     POOLS.with(|pools| {
         pools.borrow_mut().clear_new(); // `clear_new()` btmsh kolo remove law hanmsh haga specific
     });
+    POOL_SCHEDULES.with(|m| m.borrow_mut().clear_new());
 
     reset_pool_map_idx();
+    log_admin_action("reset_pools", "");
 
     Ok("✅ Tokens memory cleared".to_string())
 }
@@ -7,6 +7,7 @@ use crate::token::handlers;
 use crate::helpers::math_helpers::{price_rounded,nat_to_bigint,nat_to_decimal_precision,nat_is_zero,nat_add,nat_zero};
 use num::BigRational;
 use crate::LPToken;
+use crate::stable_schema::envelope::impl_versioned_cbor;
 
 #[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct StablePoolId(pub u32);
@@ -113,14 +114,4 @@ impl StablePool {
     
     
 }
-impl Storable for StablePool {
-    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
-        serde_cbor::to_vec(self).unwrap().into()
-    }
-
-    fn from_bytes(bytes: std::borrow::Cow<'_, [u8]>) -> Self {
-        serde_cbor::from_slice(&bytes).unwrap()
-    }
-
-    const BOUND: Bound = Bound::Unbounded;
-}
\ No newline at end of file
+impl_versioned_cbor!(StablePool);
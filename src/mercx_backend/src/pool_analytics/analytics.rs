@@ -1,19 +1,21 @@
-use crate::stable_memory::{POOLS, TOKENS, TRANSFERS};
+use crate::stable_memory::{POOLS, TOKENS};
 use crate::pool::stable_pool::StablePool;
 use crate::pool::stable_pool::StablePoolId;
 use crate::token::stable_token::StableTokenId;
 use crate::token::stable_token::StableToken;
-use crate::transfers::stable_transfer::StableTransfer;
+use crate::token::handlers::get_by_token_id;
+use crate::transfers::handlers as transfer_handlers;
+use crate::transfers::stable_transfer::{StableTransfer, TransferType};
 //use crate::transfers::tx_id::TxId;
 use crate::pool_analytics::analytics_storage::{record_pool_snapshot};
 use crate::pool_analytics::token_pricing::{get_anchor_usd,get_usd_price_from_pools};
 use crate::helpers::math_helpers::{nat_add};
 use crate::ic::general::get_time;
-use crate::transfers::stable_transfer::TransferType;
 use candid::{CandidType, Nat};
 use serde::{Deserialize, Serialize};
 use ic_cdk::{update};
 use num::ToPrimitive;
+use crate::rbac::guards::is_operator;
 
 // Analytics response types
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
@@ -91,7 +93,7 @@ pub async fn get_token_price_usd(token: &StableToken) -> Result<f64, String> {
     Ok(0.0)
 }
 
- async fn token_amount_to_usd(amount: &Nat, token: &StableToken) -> Result<f64, String> {
+pub async fn token_amount_to_usd(amount: &Nat, token: &StableToken) -> Result<f64, String> {
     let price_usd = get_token_price_usd(token).await?; // Await the async call
     let amount_f64 = amount.0.to_f64().unwrap_or(0.0); // Convert the Nat (BigUint) to f64
     let decimals_factor = 10_f64.powi(token.decimals() as i32); // Calculate 10^decimals for normalization
@@ -249,104 +251,52 @@ pub async fn calculate_pool_volume(pool_id: u32, hours: u64) -> Result<PoolVolum
     let current_time = get_time();
     let time_threshold = current_time - (hours * 60 * 60 * 1_000_000_000);
 
-    // Step 1: copy pool, tokens, and transfers out synchronously
-    let (pool, pool_transfers, tokens_vec) = TRANSFERS.with(|transfers| {
-        TOKENS.with(|tokens| {
-            POOLS.with(|pools| {
-                let transfers_map = transfers.borrow();
-                let tokens_map = tokens.borrow();
-                let pools_map = pools.borrow();
-
-                let pool = pools_map
-                    .get(&StablePoolId(pool_id))
-                    .ok_or("Pool not found")?
-                    .clone();
-
-                // Collect relevant transfers
-                let mut pool_transfers = Vec::new();
-                for (_, transfer) in transfers_map.iter() {
-                    if transfer.ts >= time_threshold
-                        && matches!(transfer.transfer_type, TransferType::Swap)
-                        && (transfer.token_id == pool.token_id_0
-                            || transfer.token_id == pool.token_id_1)
-                    {
-                        pool_transfers.push(transfer.clone());
-                    }
-                }
-
-                // Copy relevant tokens into Vec
-                let mut tokens_vec = Vec::new();
-                for (id, token) in tokens_map.iter() {
-                    if id.0 == pool.token_id_0 || id.0 == pool.token_id_1 {
-                        tokens_vec.push(token.clone());
-                    }
-                }
-
-                Ok::<_, String>((pool, pool_transfers, tokens_vec))
-            })
+    // Step 1: copy the pool and its swap transfers out synchronously
+    let pool = POOLS
+        .with(|pools| pools.borrow().get(&StablePoolId(pool_id)))
+        .ok_or("Pool not found")?;
+
+    // pool_ids holds the whole route, so multi-hop swaps through this pool are included
+    let pool_transfers: Vec<StableTransfer> = transfer_handlers::get_by_time_range(time_threshold, current_time + 1)
+        .into_iter()
+        .filter(|transfer| {
+            matches!(transfer.transfer_type, TransferType::Swap) && transfer.pool_ids.contains(&pool.pool_id)
         })
-    })?;
+        .collect();
 
     // Step 2: async price calculations
     let mut volume_usd: f64 = 0.0;
     let mut fees_usd: f64 = 0.0;
     let mut transaction_count: u32 = 0;
 
-    // Group by timestamp
-    let mut timestamp_groups: std::collections::HashMap<u64, Vec<StableTransfer>> =
-        std::collections::HashMap::new();
+    // Group by request, a swap is its pay (send) and receive transfers
+    let mut requests: std::collections::BTreeMap<u64, Vec<StableTransfer>> = std::collections::BTreeMap::new();
     for transfer in pool_transfers {
-        timestamp_groups.entry(transfer.ts).or_default().push(transfer);
+        if let Some(request_id) = transfer.request_id {
+            requests.entry(request_id).or_default().push(transfer);
+        }
     }
 
-    for (_ts, transfers_at_time) in timestamp_groups {
-        let mut processed = std::collections::HashSet::new();
-
-        for i in 0..transfers_at_time.len() {
-            if processed.contains(&i) {
+    for (_request_id, transfers) in requests {
+        // failed swaps only have the pay transfer, the refund is not a Swap transfer
+        if !transfers.iter().any(|transfer| !transfer.is_send) {
+            continue;
+        }
+        // every hop of a route moves the value paid in, so a hop's volume is the pay transfer's USD value
+        // even when the pay token is not one of this pool's tokens
+        let mut counted = false;
+        for input_transfer in transfers.iter().filter(|transfer| transfer.is_send) {
+            let Some(token) = get_by_token_id(input_transfer.token_id) else {
                 continue;
+            };
+            if let Ok(swap_volume_usd) = token_amount_to_usd(&input_transfer.amount, &token).await {
+                volume_usd += swap_volume_usd;
+                fees_usd += swap_volume_usd * (pool.lp_fee_bps as f64 / 10000.0);
+                counted = true;
             }
-            let transfer1 = &transfers_at_time[i];
-
-            for j in (i + 1)..transfers_at_time.len() {
-                if processed.contains(&j) {
-                    continue;
-                }
-                let transfer2 = &transfers_at_time[j];
-
-                let is_valid_pool_swap =
-                    (transfer1.is_send
-                        && !transfer2.is_send
-                        && transfer1.token_id == pool.token_id_0
-                        && transfer2.token_id == pool.token_id_1)
-                        || (transfer1.is_send
-                            && !transfer2.is_send
-                            && transfer1.token_id == pool.token_id_1
-                            && transfer2.token_id == pool.token_id_0);
-
-                if is_valid_pool_swap {
-                    let (input_transfer, _) =
-                        if transfer1.is_send { (transfer1, transfer2) } else { (transfer2, transfer1) };
-
-                    // lookup token
-                    if let Some(token) = tokens_vec
-                        .iter()
-                        .find(|t| t.symbol() == pool.name().split('_').next().unwrap_or(""))
-                    {
-                        if let Ok(swap_volume_usd) =
-                            token_amount_to_usd(&input_transfer.amount, token).await
-                        {
-                            volume_usd += swap_volume_usd;
-                            fees_usd += swap_volume_usd * (pool.lp_fee_bps as f64 / 10000.0);
-                            transaction_count += 1;
-                        }
-                    }
-
-                    processed.insert(i);
-                    processed.insert(j);
-                    break;
-                }
-            }
+        }
+        if counted {
+            transaction_count += 1;
         }
     }
 
@@ -800,7 +750,7 @@ total_transactions_7d,
 // Add this function to your existing analytics.rs file
 
 /// Integration function to record current analytics snapshot
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_operator")]
 pub async fn record_current_analytics() -> Result<String, String> {
 let pools_tvl = get_all_pools_tvl().await;
 let mut recorded_count = 0;
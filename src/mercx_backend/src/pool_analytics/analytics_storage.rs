@@ -1,12 +1,15 @@
-use crate::stable_memory::ANALYTICS_DATA;
+use crate::stable_memory::{ANALYTICS_DATA, SNAPSHOT_ARCHIVE};
 use crate::pool_analytics::stable_analytics::{StablePoolTimeSeries, PoolAnalyticsId};
 use crate::PoolSnapshot;
 use crate::stable_mercx_settings::mercx_settings_map;
 use crate::stable_mercx_settings::mercx_settings_map::reset_analytics_map_idx;
 use crate::pool_analytics::analytics::get_pool_metrics;
 use crate::pool::handlers;
+use crate::archive::handlers::{archive_pool_series, get_archived_snapshots};
+use crate::rbac::guards::{is_admin, is_operator};
+use crate::block_log::dex_block::log_admin_action;
 /// Record TVL and volume snapshot for a specific pool
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_operator")]
 pub fn record_pool_snapshot(pool_id: u32, tvl_usd: f64, volume_24h_usd: f64) -> Result<String, String> {
     ANALYTICS_DATA.with(|data| {
         let mut analytics = data.borrow_mut();
@@ -23,6 +26,7 @@ pub fn record_pool_snapshot(pool_id: u32, tvl_usd: f64, volume_24h_usd: f64) ->
         };
         
         pool_series.add_snapshot(tvl_usd, volume_24h_usd);
+        archive_pool_series(&mut pool_series, ic_cdk::api::time());
         analytics.insert(PoolAnalyticsId(pool_id), pool_series);
         
         Ok(format!(
@@ -33,7 +37,7 @@ pub fn record_pool_snapshot(pool_id: u32, tvl_usd: f64, volume_24h_usd: f64) ->
 }
 
 /// Record snapshots for all pools at once (call this daily via timer)
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_operator")]
 pub async fn record_all_pools_snapshot() -> Result<String, String> {
     use crate::pool_analytics::analytics::{get_all_pools_tvl, calculate_pool_volume};
     
@@ -54,7 +58,7 @@ pub async fn record_all_pools_snapshot() -> Result<String, String> {
     Ok(format!("Recorded {} pool snapshots", recorded_count))
 }
 
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_operator")]
 pub async fn record_pool_snapshot2(pool_id: u32) -> Result<String, String> {
     // Get the pool
     let _pool = handlers::get_by_pool_id(pool_id)
@@ -77,6 +81,7 @@ pub async fn record_pool_snapshot2(pool_id: u32) -> Result<String, String> {
         };
         
         pool_series.add_snapshot(tvl_usd, volume_24h_usd);
+        archive_pool_series(&mut pool_series, ic_cdk::api::time());
         analytics.insert(PoolAnalyticsId(pool_id), pool_series);
         
         Ok(format!(
@@ -87,51 +92,65 @@ pub async fn record_pool_snapshot2(pool_id: u32) -> Result<String, String> {
 }
 
 
-/// Get chart data for a specific pool (time-based, in hours)
+/// Get chart data for a specific pool (time-based, in hours), archived snapshots included
 #[ic_cdk::query]
 pub fn get_pool_chart_data(pool_id: u32, hours: u64) -> Vec<(u64, f64, f64)> {
+    let cutoff_time = ic_cdk::api::time().saturating_sub(hours * 60 * 60 * 1_000_000_000);
+    let mut chart_data: Vec<(u64, f64, f64)> =
+        get_archived_snapshots(pool_id, StablePoolTimeSeries::get_day_number(cutoff_time))
+            .into_iter()
+            .filter(|s| s.timestamp >= cutoff_time)
+            .map(|s| (s.timestamp, s.tvl_usd, s.volume_24h_usd))
+            .collect();
     ANALYTICS_DATA.with(|data| {
         let analytics = data.borrow();
-        match analytics.get(&PoolAnalyticsId(pool_id)) {
-            Some(pool_series) => pool_series.get_chart_data(hours),
-            None => Vec::new(),
+        if let Some(pool_series) = analytics.get(&PoolAnalyticsId(pool_id)) {
+            chart_data.extend(pool_series.get_chart_data(hours));
         }
-    })
+    });
+    chart_data
 }
 
-/// Get daily chart data for a specific pool (day-based, better for daily charts)
+/// Get daily chart data for a specific pool (day-based, better for daily charts), archived snapshots included
 /// Returns: Vec<(timestamp, day_number, tvl_usd, volume_24h_usd)>
 #[ic_cdk::query]
 pub fn get_pool_daily_chart(pool_id: u32, days: u64) -> Vec<(u64, u64, f64, f64)> {
+    let current_day = StablePoolTimeSeries::get_day_number(ic_cdk::api::time());
+    let mut chart_data: Vec<(u64, u64, f64, f64)> = get_archived_snapshots(pool_id, current_day.saturating_sub(days))
+        .into_iter()
+        .map(|s| (s.timestamp, s.day_number, s.tvl_usd, s.volume_24h_usd))
+        .collect();
     ANALYTICS_DATA.with(|data| {
         let analytics = data.borrow();
-        match analytics.get(&PoolAnalyticsId(pool_id)) {
-            Some(pool_series) => pool_series.get_daily_chart_data(days),
-            None => Vec::new(),
+        if let Some(pool_series) = analytics.get(&PoolAnalyticsId(pool_id)) {
+            chart_data.extend(pool_series.get_daily_chart_data(days));
         }
-    })
+    });
+    chart_data
 }
 
-/// Get all snapshots for a pool (all 30 days)
+/// Get all snapshots for a pool, the archive first and then the hot days
 #[ic_cdk::query]
 pub fn get_all_pool_snapshots(pool_id: u32) -> Vec<PoolSnapshot> {
+    let mut snapshots = get_archived_snapshots(pool_id, 0);
     ANALYTICS_DATA.with(|data| {
         let analytics = data.borrow();
-        match analytics.get(&PoolAnalyticsId(pool_id)) {
-            Some(pool_series) => pool_series.get_all_snapshots(),
-            None => Vec::new(),
+        if let Some(pool_series) = analytics.get(&PoolAnalyticsId(pool_id)) {
+            snapshots.extend(pool_series.get_all_snapshots());
         }
-    })
+    });
+    snapshots
 }
 
-/// Get snapshot count for a pool
+/// Get snapshot count for a pool, archived snapshots included
 #[ic_cdk::query]
 pub fn get_pool_snapshot_count(pool_id: u32) -> u32 {
+    let archived = get_archived_snapshots(pool_id, 0).len() as u32;
     ANALYTICS_DATA.with(|data| {
         let analytics = data.borrow();
         match analytics.get(&PoolAnalyticsId(pool_id)) {
-            Some(pool_series) => pool_series.snapshots.len() as u32,
-            None => 0,
+            Some(pool_series) => archived + pool_series.snapshots.len() as u32,
+            None => archived,
         }
     })
 }
@@ -212,7 +231,7 @@ pub fn verify_analytics_persistence() -> String {
 }
 
 /// Clear all analytics data (use with caution!)
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_admin")]
 pub fn clear_analytics_data() -> String {
     ANALYTICS_DATA.with(|data| {
         let mut analytics = data.borrow_mut();
@@ -221,18 +240,21 @@ pub fn clear_analytics_data() -> String {
             analytics.remove(&key);
         }
     });
+    log_admin_action("clear_analytics_data", "");
     "Analytics data cleared".to_string()
 }
 
 //cargo build --release --features prod
 #[cfg(not(feature = "prod"))]
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_admin")]
 fn reset_analytics() -> Result<String, String> {
     ANALYTICS_DATA.with(|analytics| {
         analytics.borrow_mut().clear_new(); // `clear_new()` btmsh kolo remove law hanmsh haga specific
     });
+    SNAPSHOT_ARCHIVE.with(|m| m.borrow_mut().clear_new());
 
     reset_analytics_map_idx();
+    log_admin_action("reset_analytics", "");
 
     Ok("✅ Analytics data cleared".to_string())
 }
\ No newline at end of file
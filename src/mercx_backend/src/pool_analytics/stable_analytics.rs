@@ -3,10 +3,11 @@ use serde::{Deserialize, Serialize};
 use ic_stable_structures::{storable::Bound, Storable};
 use std::borrow::Cow;
 use ic_cdk::api::time; //icp time
+use crate::stable_schema::envelope::impl_versioned_cbor;
 
-const MAX_DAYS_HISTORY: usize = 30;
+pub const DEFAULT_SNAPSHOT_RETENTION_DAYS: u32 = 30; // days kept in ANALYTICS_DATA, older ones go to SNAPSHOT_ARCHIVE
 const NANOSECONDS_PER_DAY: u64 = 24 * 60 * 60 * 1_000_000_000;
-const CAIRO_TIMEZONE_OFFSET_NANOS: u64 = 3 * 60 * 60 * 1_000_000_000; // +3 hours for Cairo (UTC+3)
+pub const CAIRO_TIMEZONE_OFFSET_NANOS: u64 = 3 * 60 * 60 * 1_000_000_000; // +3 hours for Cairo (UTC+3)
 
 
 #[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -33,14 +34,13 @@ pub struct PoolSnapshot {
     pub volume_24h_usd: f64,
 }
 
-#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
-pub struct StablePoolTimeSeries {
-    pub pool_id: u32,
-    pub snapshots: Vec<PoolSnapshot>,
-    pub last_updated: u64,
-}
+impl_versioned_cbor!(PoolSnapshot);
+
+/// (pool_id, day_number) key of SNAPSHOT_ARCHIVE, one snapshot per pool per day
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct PoolSnapshotKey(pub u32, pub u64);
 
-impl Storable for StablePoolTimeSeries {
+impl Storable for PoolSnapshotKey {
     fn to_bytes(&self) -> Cow<'_, [u8]> {
         serde_cbor::to_vec(self).unwrap().into()
     }
@@ -52,6 +52,15 @@ impl Storable for StablePoolTimeSeries {
     const BOUND: Bound = Bound::Unbounded;
 }
 
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StablePoolTimeSeries {
+    pub pool_id: u32,
+    pub snapshots: Vec<PoolSnapshot>,
+    pub last_updated: u64,
+}
+
+impl_versioned_cbor!(StablePoolTimeSeries);
+
 impl StablePoolTimeSeries {
     pub fn new(pool_id: u32) -> Self {
         Self {
@@ -62,7 +71,7 @@ impl StablePoolTimeSeries {
     }
 
     // Convert UTC timestamp to Cairo timezone day number
-    fn get_day_number(timestamp: u64) -> u64 {
+    pub fn get_day_number(timestamp: u64) -> u64 {
         let cairo_time = timestamp.saturating_add(CAIRO_TIMEZONE_OFFSET_NANOS);
         cairo_time / NANOSECONDS_PER_DAY
     }
@@ -71,10 +80,6 @@ impl StablePoolTimeSeries {
         let current_time = time();
         let current_day = Self::get_day_number(current_time);
         
-        // Remove snapshots older than 30 days (Cairo timezone)
-        let cutoff_day = current_day.saturating_sub(MAX_DAYS_HISTORY as u64);
-        self.snapshots.retain(|s| s.day_number >= cutoff_day);
-        
         // Check if we already have a snapshot for today (Cairo timezone)
         if let Some(today_snapshot) = self.snapshots.iter_mut().find(|s| s.day_number == current_day) {
             // Update existing snapshot for today
@@ -99,6 +104,13 @@ impl StablePoolTimeSeries {
         self.last_updated = current_time;
     }
 
+    /// Remove and return the snapshots older than cutoff_day (Cairo timezone), oldest first
+    pub fn split_off_before(&mut self, cutoff_day: u64) -> Vec<PoolSnapshot> {
+        let keep_from = self.snapshots.partition_point(|s| s.day_number < cutoff_day);
+        let kept = self.snapshots.split_off(keep_from);
+        std::mem::replace(&mut self.snapshots, kept)
+    }
+
     pub fn get_chart_data(&self, hours: u64) -> Vec<(u64, f64, f64)> {
         let cutoff_time = time().saturating_sub(hours * 60 * 60 * 1_000_000_000);
         self.snapshots
@@ -122,4 +134,30 @@ impl StablePoolTimeSeries {
     pub fn get_all_snapshots(&self) -> Vec<PoolSnapshot> {
         self.snapshots.clone()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(day_number: u64) -> PoolSnapshot {
+        PoolSnapshot {
+            timestamp: day_number * NANOSECONDS_PER_DAY,
+            day_number,
+            pool_id: 1,
+            tvl_usd: 0.0,
+            volume_24h_usd: 0.0,
+        }
+    }
+
+    #[test]
+    fn split_off_before_returns_old_snapshots_and_keeps_the_rest() {
+        let mut series = StablePoolTimeSeries::new(1);
+        series.snapshots = vec![snapshot(10), snapshot(11), snapshot(12)];
+
+        let old = series.split_off_before(12);
+
+        assert_eq!(old.iter().map(|s| s.day_number).collect::<Vec<_>>(), vec![10, 11]);
+        assert_eq!(series.snapshots.iter().map(|s| s.day_number).collect::<Vec<_>>(), vec![12]);
+    }
+}
@@ -0,0 +1,32 @@
+//! Guards for `#[ic_cdk::update(guard = "...")]`, they reject the call before the endpoint runs.
+//! Calls from Rust code go straight to the function and skip the guard.
+//! Every call a guard lets through is written to the audit log.
+use super::role::Role;
+use super::roles_map::has_role;
+use crate::audit::audit_log::record_call;
+
+fn require(role: Role) -> Result<(), String> {
+    let caller = ic_cdk::caller();
+    if has_role(caller, role) {
+        record_call();
+        Ok(())
+    } else {
+        Err(format!("❌ {} is missing the {:?} role", caller, role))
+    }
+}
+
+pub fn is_controller() -> Result<(), String> {
+    require(Role::Controller)
+}
+
+pub fn is_admin() -> Result<(), String> {
+    require(Role::Admin)
+}
+
+pub fn is_operator() -> Result<(), String> {
+    require(Role::Operator)
+}
+
+pub fn is_compliance() -> Result<(), String> {
+    require(Role::Compliance)
+}
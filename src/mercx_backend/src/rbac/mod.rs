@@ -0,0 +1,3 @@
+pub mod role;
+pub mod roles_map;
+pub mod guards;
@@ -0,0 +1,40 @@
+use candid::{CandidType, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+/// Controller covers every role and Admin covers Operator and Compliance
+#[derive(CandidType, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Role {
+    Controller, // the canister's controllers, never stored
+    Admin,      // tokens, pools, resets, fee withdrawals and granting the roles below
+    Operator,   // snapshots, metadata refresh and archive runs
+    Compliance, // whitelist and user history lookups
+}
+
+impl Role {
+    /// Roles that include this one, itself first
+    pub fn granted_by(&self) -> &'static [Role] {
+        match self {
+            Role::Controller => &[Role::Controller],
+            Role::Admin => &[Role::Admin, Role::Controller],
+            Role::Operator => &[Role::Operator, Role::Admin, Role::Controller],
+            Role::Compliance => &[Role::Compliance, Role::Admin, Role::Controller],
+        }
+    }
+}
+
+/// (principal, role) key of ROLES
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableRoleKey(pub Principal, pub Role);
+
+impl Storable for StableRoleKey {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<'_, [u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
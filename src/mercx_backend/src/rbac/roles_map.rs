@@ -0,0 +1,101 @@
+use candid::Principal;
+
+use super::guards::is_admin;
+use super::role::{Role, StableRoleKey};
+use crate::block_log::dex_block::log_admin_action;
+use crate::stable_memory::ROLES;
+use crate::audit::audit_log;
+use crate::ic::canister_ids;
+
+/// Roles stored for a principal. Controller is never stored, it comes from the canister's controllers
+pub fn get_roles(principal: Principal) -> Vec<Role> {
+    ROLES.with(|m| {
+        m.borrow()
+            .range(StableRoleKey(principal, Role::Controller)..=StableRoleKey(principal, Role::Compliance))
+            .map(|(key, _)| key.1)
+            .collect()
+    })
+}
+
+/// The canister's controllers hold every role whether or not they are stored
+pub fn has_role(principal: Principal, role: Role) -> bool {
+    if ic_cdk::api::is_controller(&principal) {
+        return true;
+    }
+    ROLES.with(|m| {
+        let map = m.borrow();
+        role.granted_by().iter().any(|r| map.contains_key(&StableRoleKey(principal, *r)))
+    })
+}
+
+pub fn insert(principal: Principal, role: Role) {
+    ROLES.with(|m| m.borrow_mut().insert(StableRoleKey(principal, role), ()));
+}
+
+pub fn remove(principal: Principal, role: Role) {
+    ROLES.with(|m| m.borrow_mut().remove(&StableRoleKey(principal, role)));
+}
+
+/// Seed the stored roles on init and every upgrade. Controller rows recorded by older builds are
+/// dropped, a principal removed from the canister's controllers must not keep the role
+pub fn bootstrap_roles(action: &str) {
+    let caller = ic_cdk::caller();
+    let controller_keys: Vec<StableRoleKey> = ROLES.with(|m| {
+        m.borrow()
+            .iter()
+            .map(|(key, _)| key)
+            .filter(|key| key.1 == Role::Controller)
+            .collect()
+    });
+    for key in controller_keys {
+        ROLES.with(|m| m.borrow_mut().remove(&key));
+    }
+    // verify_kyc of the KYC canister calls add_to_whitelist, which needs the Compliance role
+    insert(canister_ids::kyc_canister(), Role::Compliance);
+    audit_log::record(caller, action, String::new());
+}
+
+/// The is_admin guard already recorded the call, so this only checks roles
+fn check_can_manage(role: Role) -> Result<(), String> {
+    let caller = ic_cdk::caller();
+    let required = if role == Role::Controller { Role::Controller } else { Role::Admin };
+    if !has_role(caller, required) {
+        Err(format!("❌ {} is missing the {:?} role", caller, required))?
+    }
+    Ok(())
+}
+
+#[ic_cdk::update(guard = "is_admin")]
+fn grant_role(principal: Principal, role: Role) -> Result<(), String> {
+    check_can_manage(role)?;
+    if role == Role::Controller {
+        Err("❌ Controller is held by the canister's controllers, add the principal as a controller instead")?
+    }
+    if principal == Principal::anonymous() {
+        Err("❌ Roles can not be granted to the anonymous principal")?
+    }
+    insert(principal, role);
+    log_admin_action("grant_role", &format!("{} {:?}", principal, role));
+    Ok(())
+}
+
+#[ic_cdk::update(guard = "is_admin")]
+fn revoke_role(principal: Principal, role: Role) -> Result<(), String> {
+    check_can_manage(role)?;
+    let removed = ROLES.with(|m| m.borrow_mut().remove(&StableRoleKey(principal, role)));
+    if removed.is_none() {
+        Err(format!("❌ {} does not have the {:?} role", principal, role))?
+    }
+    log_admin_action("revoke_role", &format!("{} {:?}", principal, role));
+    Ok(())
+}
+
+#[ic_cdk::query]
+fn get_my_roles() -> Vec<Role> {
+    get_roles(ic_cdk::caller())
+}
+
+#[ic_cdk::query(guard = "is_admin")]
+fn get_role_members() -> Vec<(Principal, Role)> {
+    ROLES.with(|m| m.borrow().iter().map(|(key, _)| (key.0, key.1)).collect())
+}
@@ -1,5 +1,7 @@
 use crate::helpers::math_helpers::{nat_divide, nat_is_zero, nat_multiply, nat_subtract, nat_zero,nat_add};
 use crate::kyc::kyc_id::get_user_by_caller;
+use crate::compliance::rules;
+use crate::ic::address::Address;
 use crate::pool::handlers;
 use crate::remove_liquidity::remove_liquidity_args::RemoveLiquidityArgs;
 use crate::stable_lp_token::lp_token_map;
@@ -8,12 +10,14 @@ use crate::{StableLPToken, StablePool, StableToken,LPToken};
 use crate::transfers::tx_id::TxId;
 use crate::transfers::stable_transfer::{StableTransfer,TransferType};
 use crate::ic::transfer::icrc1_transfer;
-use crate::ic::id::caller_id;
+use crate::ic::id::{caller, caller_id};
+use crate::balances::balance_map::credit;
 use icrc_ledger_types::icrc1::account::Account;
 use crate::remove_liquidity::remove_liquidity_reply_helpers::{to_remove_liquidity_reply_failed,to_remove_liquidity_reply};
 use crate::remove_liquidity::remove_liquidity_reply::RemoveLiquidityReply;
 use crate::ic::general::get_time;
-use candid::Nat;
+use candid::{Nat, Principal};
+use crate::stable_tx::{remove_liquidity_tx::RemoveLiquidityTx, stable_tx::StableTx, tx_map};
 
 //to calculate how many tokens in the pool and lp_fees the user will recieve
 pub fn calculate_amounts(
@@ -137,6 +141,10 @@ async fn check_arguments(
         payout_amount_1,
         payout_lp_fee_1,
     ) = check_arguments_with_user(args, user_id).await?;
+    // payouts go to the caller, on the ledger or as internal balance
+    let recipient = Address::PrincipalId(caller_id());
+    rules::check_recipient(&pool.token_0(), &recipient, &nat_add(&payout_amount_0, &payout_lp_fee_0)).await?;
+    rules::check_recipient(&pool.token_1(), &recipient, &nat_add(&payout_amount_1, &payout_lp_fee_1)).await?;
 
     Ok((
         user_id,
@@ -244,6 +252,10 @@ async fn transfer_token(
                 tx_id: TxId::BlockIndex(block_id),
                 transfer_type: TransferType::LiquidityRemove,  // Add this line
                 ts,
+                account: Some(*to_principal_id),
+                pool_ids: Vec::new(),
+                request_id: None,
+                usd_price: None,
             });
             transfer_ids.push(transfer_id);
      
@@ -303,8 +315,20 @@ async fn send_payout_tokens(
         ts,
     )
     .await;
+    transfer_handlers::link(&transfer_ids, &[pool.pool_id]);
 
     let reply = if !transfer_ids.is_empty() {
+        insert_remove_liquidity_tx(
+            to_principal_id.owner,
+            pool,
+            payout_amount_0,
+            payout_lp_fee_0,
+            payout_amount_1,
+            payout_lp_fee_1,
+            remove_lp_token_amount,
+            &transfer_ids,
+            ts,
+        );
         to_remove_liquidity_reply(
             pool.pool_id,
             payout_amount_0.clone(),
@@ -323,6 +347,33 @@ async fn send_payout_tokens(
 }
 
 
+#[allow(clippy::too_many_arguments)]
+fn insert_remove_liquidity_tx(
+    user: Principal,
+    pool: &StablePool,
+    payout_amount_0: &Nat,
+    payout_lp_fee_0: &Nat,
+    payout_amount_1: &Nat,
+    payout_lp_fee_1: &Nat,
+    remove_lp_token_amount: &Nat,
+    transfer_ids: &[u64],
+    ts: u64,
+) {
+    tx_map::record(&StableTx::RemoveLiquidity(RemoveLiquidityTx {
+        tx_id: 0,
+        user,
+        pool_id: pool.pool_id,
+        amount_0: payout_amount_0.clone(),
+        lp_fee_0: payout_lp_fee_0.clone(),
+        amount_1: payout_amount_1.clone(),
+        lp_fee_1: payout_lp_fee_1.clone(),
+        remove_lp_token_amount: remove_lp_token_amount.clone(),
+        transfer_ids: transfer_ids.to_vec(),
+        ts,
+        usd_value: None,
+    }));
+}
+
 //This return_lp_token function is responsible for returning LP tokens to a user — typically when a liquidity removal fails and you want to give the LP tokens back to the user.
 //it calculates and update stable lp 
 fn return_lp_token(user_id: u32, lp_token: &LPToken, remove_lp_token_amount: &Nat, ts: u64) -> Result<(), String> {
@@ -486,4 +537,41 @@ pub async fn remove_liquidity_from_pool(
     };
 
     result
-}
\ No newline at end of file
+}
+/// remove liquidity with the payout credited to the caller's internal balance, no ledger calls
+/// - payout_amount + payout_lp_fee is credited in full as no gas is needed
+#[ic_cdk::update]
+pub async fn remove_liquidity_to_balance(args: RemoveLiquidityArgs) -> Result<RemoveLiquidityReply, String> {
+    let (user_id, pool, remove_lp_token_amount, payout_amount_0, payout_lp_fee_0, payout_amount_1, payout_lp_fee_1) =
+        check_arguments(&args).await?;
+    let ts = get_time();
+    let owner = caller();
+
+    remove_lp_token(user_id, &pool.lp_token(), &remove_lp_token_amount, ts)?;
+    update_liquidity_pool(&pool, &payout_amount_0, &payout_lp_fee_0, &payout_amount_1, &payout_lp_fee_1);
+
+    credit(owner, pool.token_id_0, &nat_add(&payout_amount_0, &payout_lp_fee_0), ts);
+    credit(owner, pool.token_id_1, &nat_add(&payout_amount_1, &payout_lp_fee_1), ts);
+    insert_remove_liquidity_tx(
+        owner,
+        &pool,
+        &payout_amount_0,
+        &payout_lp_fee_0,
+        &payout_amount_1,
+        &payout_lp_fee_1,
+        &remove_lp_token_amount,
+        &[],
+        ts,
+    );
+
+    Ok(to_remove_liquidity_reply(
+        pool.pool_id,
+        payout_amount_0,
+        payout_lp_fee_0,
+        payout_amount_1,
+        payout_lp_fee_1,
+        remove_lp_token_amount,
+        Vec::new(),
+        ts,
+    ))
+}
@@ -5,7 +5,7 @@ use serde::{Deserialize, Serialize};
 /// Used in StableRequest
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
 pub struct RemoveLiquidityArgs {
-    pub token_0: String,
-    pub token_1: String,
+    pub token_0: String, // symbol or IC.<canister_id>
+    pub token_1: String, // symbol or IC.<canister_id>
     pub remove_lp_token_amount: Nat,
 }
\ No newline at end of file
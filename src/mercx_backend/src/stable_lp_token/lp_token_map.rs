@@ -10,6 +10,8 @@ use crate::stable_mercx_settings::mercx_settings_map::reset_lp_map_idx;
 use crate::stable_memory::LPMETADATA;
 use crate::lp_metadata::stable_lp_metadata::StableLpMetadataId;
 use candid::CandidType;use serde::Serialize; use candid::Deserialize;
+use crate::rbac::guards::is_admin;
+use crate::block_log::dex_block::log_admin_action;
 
 /// get lp_token of the caller
  #[ic_cdk::query]
@@ -167,13 +169,14 @@ pub fn update(lp_token: &StableLPToken) {
 }
 
 #[cfg(not(feature = "prod"))]
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_admin")]
 fn reset_lp() -> Result<String, String> {
     LP_TOKEN_MAP.with(|tokens| {
         tokens.borrow_mut().clear_new(); // `clear_new()` btmsh kolo remove law hanmsh haga specific
     });
 
     reset_lp_map_idx();
+    log_admin_action("reset_lp", "");
 
     Ok("✅ lp memory cleared".to_string())
 }
\ No newline at end of file
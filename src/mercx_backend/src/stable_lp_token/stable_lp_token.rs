@@ -2,6 +2,7 @@
 use candid::{CandidType, Nat,Principal};
 use ic_stable_structures::{storable::Bound, Storable};
 use serde::{Deserialize, Serialize};
+use crate::stable_schema::envelope::impl_versioned_cbor;
 
 #[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct StableLPTokenId(pub u64);
@@ -41,14 +42,4 @@ impl StableLPToken {
     }
 }
 
-impl Storable for StableLPToken {
-    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
-        serde_cbor::to_vec(self).unwrap().into()
-    }
-
-    fn from_bytes(bytes: std::borrow::Cow<'_, [u8]>) -> Self {
-        serde_cbor::from_slice(&bytes).unwrap()
-    }
-
-    const BOUND: Bound = Bound::Unbounded;
-}
\ No newline at end of file
+impl_versioned_cbor!(StableLPToken);
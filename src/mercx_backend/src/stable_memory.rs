@@ -3,6 +3,7 @@ use ic_stable_structures::{DefaultMemoryImpl, StableBTreeMap, StableCell};
 use std::cell::RefCell;
 
 use crate::transfers::stable_transfer::{StableTransferId,StableTransfer};
+use crate::transfers::stable_transfer_index::{StableTransferTxKey, StableTransferTsKey, StableTransferPrincipalKey};
 
 use candid::Principal;
 
@@ -14,7 +15,23 @@ use crate::lp_metadata::stable_lp_metadata::{StableLpMetadataId,LPToken};
 
 use crate::stable_lp_token::stable_lp_token::{StableLPToken,StableLPTokenId};
 
-use crate::pool_analytics::stable_analytics::{PoolAnalyticsId,StablePoolTimeSeries};
+use crate::pool_analytics::stable_analytics::{PoolAnalyticsId,StablePoolTimeSeries,PoolSnapshot,PoolSnapshotKey};
+
+use crate::balances::stable_balance::{StableBalanceId, StableBalance};
+use crate::balances::stable_pending_withdrawal::StablePendingWithdrawal;
+use crate::balances::stable_pending_deposit::StablePendingDeposit;
+
+use crate::stable_tx::stable_tx::{StableTxId, StableTx};
+use crate::stable_tx::stable_tx_index::{StableTxUserKey, StableTxPoolKey, StableTxTsKey};
+
+use crate::block_log::stable_block::{StableBlockId, StableBlock};
+use crate::rbac::role::StableRoleKey;
+use crate::audit::stable_audit_entry::{StableAuditId, StableAuditEntry};
+use crate::stable_schema::envelope::RawRecord;
+use crate::circuit_breaker::stable_circuit_breaker::StableCircuitBreaker;
+use crate::kyc::stable_kyc_user::StableKycUser;
+use crate::trading_hours::stable_trading_schedule::StableTradingSchedule;
+use ic_stable_structures::Storable;
 
 type Memory = VirtualMemory<DefaultMemoryImpl>;
 
@@ -27,6 +44,28 @@ pub const WHITELIST_MEM_ID: MemoryId = MemoryId::new(5);
 pub const LP_METADATA_MEM_ID: MemoryId = MemoryId::new(6);
 pub const LP_TOKEN_MEMORY_ID: MemoryId = MemoryId::new(7);
 pub const ANALYTICS_MEMORY_ID: MemoryId = MemoryId::new(8); 
+pub const TOKEN_SYMBOL_INDEX_MEMORY_ID: MemoryId = MemoryId::new(9);
+pub const USER_BALANCE_MEMORY_ID: MemoryId = MemoryId::new(10);
+pub const TRANSFER_TX_INDEX_MEMORY_ID: MemoryId = MemoryId::new(11);
+pub const TRANSFER_TS_INDEX_MEMORY_ID: MemoryId = MemoryId::new(12);
+pub const TRANSFER_PRINCIPAL_INDEX_MEMORY_ID: MemoryId = MemoryId::new(13);
+pub const TX_MEMORY_ID: MemoryId = MemoryId::new(14);
+pub const TX_USER_INDEX_MEMORY_ID: MemoryId = MemoryId::new(15);
+pub const TX_POOL_INDEX_MEMORY_ID: MemoryId = MemoryId::new(16);
+pub const TX_TS_INDEX_MEMORY_ID: MemoryId = MemoryId::new(17);
+pub const BLOCK_MEMORY_ID: MemoryId = MemoryId::new(18);
+pub const TRANSFER_ARCHIVE_MEMORY_ID: MemoryId = MemoryId::new(19);
+pub const SNAPSHOT_ARCHIVE_MEMORY_ID: MemoryId = MemoryId::new(20);
+pub const ROLES_MEMORY_ID: MemoryId = MemoryId::new(21);
+pub const AUDIT_LOG_MEMORY_ID: MemoryId = MemoryId::new(22);
+pub const CIRCUIT_BREAKER_MEMORY_ID: MemoryId = MemoryId::new(23);
+pub const KYC_CACHE_MEMORY_ID: MemoryId = MemoryId::new(24);
+pub const TOKEN_ACQUISITIONS_MEMORY_ID: MemoryId = MemoryId::new(25);
+pub const POOL_SCHEDULE_MEMORY_ID: MemoryId = MemoryId::new(26);
+// id 2 held a map of an older release on deployed canisters, keep it unused
+pub const TOKEN_CANISTER_INDEX_MEMORY_ID: MemoryId = MemoryId::new(27);
+pub const PENDING_WITHDRAWAL_MEMORY_ID: MemoryId = MemoryId::new(28);
+pub const PENDING_DEPOSIT_MEMORY_ID: MemoryId = MemoryId::new(29);
 
 
 thread_local! {
@@ -70,5 +109,130 @@ thread_local! {
     pub static ANALYTICS_DATA: RefCell<StableBTreeMap<PoolAnalyticsId, StablePoolTimeSeries, Memory>> = RefCell::new(
         StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(ANALYTICS_MEMORY_ID)))
     );
+
+    // canister_id -> token_id
+    pub static TOKEN_CANISTER_INDEX: RefCell<StableBTreeMap<Principal, u32, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(TOKEN_CANISTER_INDEX_MEMORY_ID)))
+    );
+
+    // lowercase symbol -> token_id
+    pub static TOKEN_SYMBOL_INDEX: RefCell<StableBTreeMap<String, u32, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(TOKEN_SYMBOL_INDEX_MEMORY_ID)))
+    );
+
+    // internal trading balances credited from deposit subaccounts
+    pub static USER_BALANCES: RefCell<StableBTreeMap<StableBalanceId, StableBalance, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(USER_BALANCE_MEMORY_ID)))
+    );
+
+    // (token_id, tx_id) -> transfer_id
+    pub static TRANSFER_TX_INDEX: RefCell<StableBTreeMap<StableTransferTxKey, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(TRANSFER_TX_INDEX_MEMORY_ID)))
+    );
+
+    // (ts, transfer_id), ordered by time for range queries
+    pub static TRANSFER_TS_INDEX: RefCell<StableBTreeMap<StableTransferTsKey, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(TRANSFER_TS_INDEX_MEMORY_ID)))
+    );
+
+    // (principal, transfer_id) of the account on the user side of the transfer
+    pub static TRANSFER_PRINCIPAL_INDEX: RefCell<StableBTreeMap<StableTransferPrincipalKey, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(TRANSFER_PRINCIPAL_INDEX_MEMORY_ID)))
+    );
+
+    // typed swap and liquidity transactions
+    pub static TX_MAP: RefCell<StableBTreeMap<StableTxId, StableTx, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(TX_MEMORY_ID)))
+    );
+
+    // (user, tx_id)
+    pub static TX_USER_INDEX: RefCell<StableBTreeMap<StableTxUserKey, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(TX_USER_INDEX_MEMORY_ID)))
+    );
+
+    // (pool_id, tx_id), a multi-hop swap is indexed under every pool it went through
+    pub static TX_POOL_INDEX: RefCell<StableBTreeMap<StableTxPoolKey, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(TX_POOL_INDEX_MEMORY_ID)))
+    );
+
+    // (ts, tx_id)
+    pub static TX_TS_INDEX: RefCell<StableBTreeMap<StableTxTsKey, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(TX_TS_INDEX_MEMORY_ID)))
+    );
+
+    // hash-chained ICRC-3 block log of DEX activity
+    pub static BLOCKS: RefCell<StableBTreeMap<StableBlockId, StableBlock, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(BLOCK_MEMORY_ID)))
+    );
+
+    // transfers past the retention period, moved out of TRANSFERS. The transfer indexes still point at them
+    pub static TRANSFER_ARCHIVE: RefCell<StableBTreeMap<StableTransferId, StableTransfer, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(TRANSFER_ARCHIVE_MEMORY_ID)))
+    );
+
+    // (pool_id, day_number) -> pool snapshots past the retention period of ANALYTICS_DATA
+    pub static SNAPSHOT_ARCHIVE: RefCell<StableBTreeMap<PoolSnapshotKey, PoolSnapshot, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(SNAPSHOT_ARCHIVE_MEMORY_ID)))
+    );
+
+    // (principal, role) grants for admin endpoints
+    pub static ROLES: RefCell<StableBTreeMap<StableRoleKey, (), Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(ROLES_MEMORY_ID)))
+    );
+
+    // privileged calls, append only and never cleared by the reset endpoints
+    pub static AUDIT_LOG: RefCell<StableBTreeMap<StableAuditId, StableAuditEntry, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(AUDIT_LOG_MEMORY_ID)))
+    );
+
+    // emergency halts per subsystem and the automatic price move trigger
+    pub static CIRCUIT_BREAKER: RefCell<StableCell<StableCircuitBreaker, Memory>> = RefCell::new(
+        StableCell::init(
+            MEMORY_MANAGER.with(|m| m.borrow().get(CIRCUIT_BREAKER_MEMORY_ID)),
+            StableCircuitBreaker::default()
+        ).expect("Failed to initialize circuit breaker")
+    );
+
+    // principal -> KYC user, pushed by the KYC canister
+    pub static KYC_CACHE: RefCell<StableBTreeMap<Principal, StableKycUser, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(KYC_CACHE_MEMORY_ID)))
+    );
+
+    // (holder, token_id) -> ts of the last swap into a token with a lockup
+    pub static TOKEN_ACQUISITIONS: RefCell<StableBTreeMap<StableBalanceId, u64, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(TOKEN_ACQUISITIONS_MEMORY_ID)))
+    );
+
+    // pool_id -> trading hours, pools without one trade around the clock
+    pub static POOL_SCHEDULES: RefCell<StableBTreeMap<StablePoolId, StableTradingSchedule, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(POOL_SCHEDULE_MEMORY_ID)))
+    );
+
+    // (owner, token_id) -> withdrawal whose ledger transfer is unconfirmed, at most one per token
+    pub static PENDING_WITHDRAWALS: RefCell<StableBTreeMap<StableBalanceId, StablePendingWithdrawal, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(PENDING_WITHDRAWAL_MEMORY_ID)))
+    );
+
+    // (owner, token_id) -> deposit sweep whose ledger transfer is unconfirmed, at most one per token
+    pub static PENDING_DEPOSITS: RefCell<StableBTreeMap<StableBalanceId, StablePendingDeposit, Memory>> = RefCell::new(
+        StableBTreeMap::init(MEMORY_MANAGER.with(|m| m.borrow().get(PENDING_DEPOSIT_MEMORY_ID)))
+    );
   
-}
\ No newline at end of file
+}
+
+/// Map of memory_id opened with undecoded values, only to read records that may not decode
+pub fn with_raw_map<K: Storable + Ord + Clone, V: Storable, R>(
+    memory_id: MemoryId,
+    f: impl FnOnce(&StableBTreeMap<K, RawRecord<V>, Memory>) -> R,
+) -> R {
+    MEMORY_MANAGER.with(|m| f(&StableBTreeMap::init(m.borrow().get(memory_id))))
+}
+
+/// Stored bytes of the cell in memory_id, without decoding them
+pub fn raw_cell_bytes<V: Storable>(memory_id: MemoryId) -> Vec<u8> {
+    MEMORY_MANAGER.with(|m| {
+        StableCell::<RawRecord<V>, _>::init(m.borrow().get(memory_id), RawRecord::default())
+            .map(|cell| cell.get().bytes.clone())
+            .unwrap_or_default()
+    })
+}
@@ -0,0 +1,117 @@
+use std::fmt::Display;
+
+use super::mercx_settings_map;
+use super::settings_reply::SettingsReply;
+use crate::audit::audit_log;
+use crate::block_log::dex_block::log_admin_action;
+use crate::rbac::guards::is_admin;
+
+const MIN_TRANSFER_EXPIRY_NANOSECS: u64 = 60_000_000_000; // 1 minute
+const MAX_TRANSFER_EXPIRY_NANOSECS: u64 = 86_400_000_000_000; // 1 day
+
+fn to_settings_reply() -> SettingsReply {
+    let settings = mercx_settings_map::get();
+    SettingsReply {
+        mercx_backend: settings.mercx_backend,
+        default_lp_fee_bps: settings.default_lp_fee_bps,
+        default_mercx_fee_bps: settings.default_mercx_fee_bps,
+        transfer_expiry_nanosecs: settings.transfer_expiry_nanosecs,
+        default_max_slippage: settings.default_max_slippage,
+        transfer_retention_days: settings.transfer_retention_days,
+        snapshot_retention_days: settings.snapshot_retention_days,
+    }
+}
+
+/// Keep the old and new value in the audit log and the ICRC-3 block log
+pub(crate) fn record_change<T: Display>(setting: &str, old: T, new: T) {
+    let detail = format!("{}: {} -> {}", setting, old, new);
+    ic_cdk::println!("⚙️ Setting changed, {}", detail);
+    audit_log::record(ic_cdk::caller(), "settings_change", detail.clone());
+    log_admin_action("settings_change", &detail);
+}
+
+// the LP fee includes the Mercx fee, add_pool rejects pools where it is lower
+fn validate_fees(lp_fee_bps: u8, mercx_fee_bps: u8) -> Result<(), String> {
+    if lp_fee_bps < mercx_fee_bps {
+        Err(format!(
+            "❌ LP fee of {} bps can not be less than the Mercx fee of {} bps",
+            lp_fee_bps, mercx_fee_bps
+        ))?
+    }
+    Ok(())
+}
+
+fn validate_transfer_expiry(transfer_expiry_nanosecs: u64) -> Result<(), String> {
+    if !(MIN_TRANSFER_EXPIRY_NANOSECS..=MAX_TRANSFER_EXPIRY_NANOSECS).contains(&transfer_expiry_nanosecs) {
+        Err(format!(
+            "❌ Transfer expiry must be between {} and {} nanoseconds",
+            MIN_TRANSFER_EXPIRY_NANOSECS, MAX_TRANSFER_EXPIRY_NANOSECS
+        ))?
+    }
+    Ok(())
+}
+
+fn validate_max_slippage(max_slippage: f64) -> Result<(), String> {
+    if !max_slippage.is_finite() || max_slippage <= 0.0 || max_slippage > 100.0 {
+        Err("❌ Max slippage must be above 0 and at most 100 percent")?
+    }
+    Ok(())
+}
+
+#[ic_cdk::query]
+fn get_settings() -> SettingsReply {
+    to_settings_reply()
+}
+
+#[ic_cdk::update(guard = "is_admin")]
+fn set_default_lp_fee_bps(default_lp_fee_bps: u8) -> Result<SettingsReply, String> {
+    let settings = mercx_settings_map::get();
+    validate_fees(default_lp_fee_bps, settings.default_mercx_fee_bps)?;
+    mercx_settings_map::set_default_lp_fee_bps(default_lp_fee_bps);
+    record_change("default_lp_fee_bps", settings.default_lp_fee_bps, default_lp_fee_bps);
+    Ok(to_settings_reply())
+}
+
+#[ic_cdk::update(guard = "is_admin")]
+fn set_default_mercx_fee_bps(default_mercx_fee_bps: u8) -> Result<SettingsReply, String> {
+    let settings = mercx_settings_map::get();
+    validate_fees(settings.default_lp_fee_bps, default_mercx_fee_bps)?;
+    mercx_settings_map::set_default_mercx_fee_bps(default_mercx_fee_bps);
+    record_change("default_mercx_fee_bps", settings.default_mercx_fee_bps, default_mercx_fee_bps);
+    Ok(to_settings_reply())
+}
+
+#[ic_cdk::update(guard = "is_admin")]
+fn set_transfer_expiry_nanosecs(transfer_expiry_nanosecs: u64) -> Result<SettingsReply, String> {
+    validate_transfer_expiry(transfer_expiry_nanosecs)?;
+    let old = mercx_settings_map::get().transfer_expiry_nanosecs;
+    mercx_settings_map::set_transfer_expiry_nanosecs(transfer_expiry_nanosecs);
+    record_change("transfer_expiry_nanosecs", old, transfer_expiry_nanosecs);
+    Ok(to_settings_reply())
+}
+
+#[ic_cdk::update(guard = "is_admin")]
+fn set_default_max_slippage(default_max_slippage: f64) -> Result<SettingsReply, String> {
+    validate_max_slippage(default_max_slippage)?;
+    let old = mercx_settings_map::get().default_max_slippage;
+    mercx_settings_map::set_default_max_slippage(default_max_slippage);
+    record_change("default_max_slippage", old, default_max_slippage);
+    Ok(to_settings_reply())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_are_validated() {
+        assert!(validate_fees(30, 5).is_ok());
+        assert!(validate_fees(4, 5).is_err());
+        assert!(validate_transfer_expiry(3_600_000_000_000).is_ok());
+        assert!(validate_transfer_expiry(1_000).is_err());
+        assert!(validate_max_slippage(100.0).is_ok());
+        assert!(validate_max_slippage(0.0).is_err());
+        assert!(validate_max_slippage(f64::NAN).is_err());
+        assert!(validate_max_slippage(100.5).is_err());
+    }
+}
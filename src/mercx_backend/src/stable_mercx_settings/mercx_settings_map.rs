@@ -1,5 +1,8 @@
 use crate::stable_mercx_settings::stable_mercx_settings::StableMercxSettings;
 use crate::stable_memory::MERCX_SETTINGS;
+use crate::ic::canister_ids::CanisterIds;
+use crate::compliance::compliance_policy::CompliancePolicy;
+use icrc_ledger_types::icrc1::account::Account;
 
 
 pub fn get() -> StableMercxSettings {
@@ -116,6 +119,34 @@ pub fn inc_transfer_map_idx() -> u64 {
 
 
 
+pub fn inc_request_map_idx() -> u64 {
+    MERCX_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let mercx_settings = map.get();
+        let request_map_idx = mercx_settings.request_map_idx + 1;
+        let new_mercx_settings = StableMercxSettings {
+            request_map_idx,
+            ..mercx_settings.clone()
+        };
+        _ = map.set(new_mercx_settings);
+        request_map_idx
+    })
+}
+
+pub fn inc_tx_map_idx() -> u64 {
+    MERCX_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let mercx_settings = map.get();
+        let tx_map_idx = mercx_settings.tx_map_idx + 1;
+        let new_mercx_settings = StableMercxSettings {
+            tx_map_idx,
+            ..mercx_settings.clone()
+        };
+        _ = map.set(new_mercx_settings);
+        tx_map_idx
+    })
+}
+
 pub fn reset_lp_map_idx() {
     MERCX_SETTINGS.with(|s| {
         let mut map = s.borrow_mut();
@@ -182,4 +213,113 @@ pub fn reset_analytics_map_idx() {
         };
         _ = map.set(new_settings);
     });
-} 
\ No newline at end of file
+}
+
+pub fn set_retention_days(transfer_retention_days: u32, snapshot_retention_days: u32) {
+    MERCX_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let current_settings = map.get();
+        let new_settings = StableMercxSettings {
+            transfer_retention_days: Some(transfer_retention_days),
+            snapshot_retention_days: Some(snapshot_retention_days),
+            ..current_settings.clone()
+        };
+        _ = map.set(new_settings);
+    });
+}
+
+pub fn set_canister_ids(canister_ids: CanisterIds) {
+    MERCX_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let current_settings = map.get();
+        let new_settings = StableMercxSettings {
+            canister_ids,
+            ..current_settings.clone()
+        };
+        _ = map.set(new_settings);
+    });
+}
+
+pub fn set_mercx_backend(mercx_backend: Account) {
+    MERCX_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let current_settings = map.get();
+        let new_settings = StableMercxSettings {
+            mercx_backend,
+            ..current_settings.clone()
+        };
+        _ = map.set(new_settings);
+    });
+}
+
+pub fn set_schema_version(schema_version: u32) {
+    MERCX_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let current_settings = map.get();
+        let new_settings = StableMercxSettings {
+            schema_version,
+            ..current_settings.clone()
+        };
+        _ = map.set(new_settings);
+    });
+}
+
+pub fn set_default_lp_fee_bps(default_lp_fee_bps: u8) {
+    MERCX_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let current_settings = map.get();
+        let new_settings = StableMercxSettings {
+            default_lp_fee_bps,
+            ..current_settings.clone()
+        };
+        _ = map.set(new_settings);
+    });
+}
+
+pub fn set_default_mercx_fee_bps(default_mercx_fee_bps: u8) {
+    MERCX_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let current_settings = map.get();
+        let new_settings = StableMercxSettings {
+            default_mercx_fee_bps,
+            ..current_settings.clone()
+        };
+        _ = map.set(new_settings);
+    });
+}
+
+pub fn set_transfer_expiry_nanosecs(transfer_expiry_nanosecs: u64) {
+    MERCX_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let current_settings = map.get();
+        let new_settings = StableMercxSettings {
+            transfer_expiry_nanosecs,
+            ..current_settings.clone()
+        };
+        _ = map.set(new_settings);
+    });
+}
+
+pub fn set_default_max_slippage(default_max_slippage: f64) {
+    MERCX_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let current_settings = map.get();
+        let new_settings = StableMercxSettings {
+            default_max_slippage,
+            ..current_settings.clone()
+        };
+        _ = map.set(new_settings);
+    });
+}
+
+pub fn set_compliance_policy(compliance_policy: CompliancePolicy) {
+    MERCX_SETTINGS.with(|s| {
+        let mut map = s.borrow_mut();
+        let current_settings = map.get();
+        let new_settings = StableMercxSettings {
+            compliance_policy,
+            ..current_settings.clone()
+        };
+        _ = map.set(new_settings);
+    });
+}
@@ -1,2 +1,4 @@
 pub mod stable_mercx_settings;
-pub mod mercx_settings_map;
\ No newline at end of file
+pub mod mercx_settings_map;
+pub mod handlers;
+pub mod settings_reply;
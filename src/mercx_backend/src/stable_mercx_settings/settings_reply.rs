@@ -0,0 +1,15 @@
+use candid::CandidType;
+use icrc_ledger_types::icrc1::account::Account;
+use serde::{Deserialize, Serialize};
+
+/// The tunable part of StableMercxSettings, the map counters stay internal
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct SettingsReply {
+    pub mercx_backend: Account,
+    pub default_lp_fee_bps: u8,
+    pub default_mercx_fee_bps: u8,
+    pub transfer_expiry_nanosecs: u64,
+    pub default_max_slippage: f64, // percent
+    pub transfer_retention_days: Option<u32>,
+    pub snapshot_retention_days: Option<u32>,
+}
@@ -1,11 +1,14 @@
 use candid::{CandidType,Principal};
-use ic_stable_structures::{storable::Bound, Storable};
 use serde::{Deserialize, Serialize};
-use crate::stable_memory::{TOKENS,POOLS,TRANSFERS,LPMETADATA};
+use crate::stable_memory::{TOKENS,POOLS,TRANSFERS,TRANSFER_ARCHIVE,LPMETADATA};
 use icrc_ledger_types::icrc1::account::Account;
 use crate::ic::canister_address::MERCX_BACKEND;
+use crate::ic::canister_ids::CanisterIds;
+use crate::compliance::compliance_policy::CompliancePolicy;
 use crate::stable_memory::LP_TOKEN_MAP;
 use crate::stable_memory::ANALYTICS_DATA;
+use crate::stable_schema::envelope::impl_versioned_cbor;
+use crate::stable_schema::migrations::latest_migration_id;
 
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
 pub struct StableMercxSettings {
@@ -20,6 +23,20 @@ pub struct StableMercxSettings {
     pub default_max_slippage: f64,
     pub lp_token_map_idx: u64, // counter for LP_TOKEN_MAP
     pub analytics_map_idx: u32, // NEW: counter for ANALYTICS_DATA
+    #[serde(default)]
+    pub request_map_idx: u64, // counter for StableTransfer.request_id
+    #[serde(default)]
+    pub tx_map_idx: u64, // counter for TX_MAP
+    #[serde(default)]
+    pub transfer_retention_days: Option<u32>, // transfers older than this move to TRANSFER_ARCHIVE, None = archive default
+    #[serde(default)]
+    pub snapshot_retention_days: Option<u32>, // pool snapshots older than this move to SNAPSHOT_ARCHIVE
+    #[serde(default)]
+    pub canister_ids: CanisterIds, // set from init/upgrade args and set_canister_ids
+    #[serde(default)]
+    pub schema_version: u32, // last stable memory migration applied, 0 for settings stored before migrations
+    #[serde(default)]
+    pub compliance_policy: CompliancePolicy, // who may swap, add liquidity and create pools
 
 }
 
@@ -28,7 +45,8 @@ impl Default for StableMercxSettings {
         let token_map_idx = TOKENS.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
         let lp_metadata_map_idx = LPMETADATA.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
         let pool_map_idx = POOLS.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
-        let transfer_map_idx = TRANSFERS.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
+        let transfer_map_idx = TRANSFERS.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0))
+            .max(TRANSFER_ARCHIVE.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0)));
         let lp_token_map_idx = LP_TOKEN_MAP.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0));
         let analytics_map_idx = ANALYTICS_DATA.with(|m| m.borrow().iter().map(|(k, _)| k.0).max().unwrap_or(0)); // NEW
 
@@ -45,18 +63,15 @@ impl Default for StableMercxSettings {
             default_max_slippage: 2.0_f64,
             lp_token_map_idx,
             analytics_map_idx,
+            request_map_idx: 0,
+            tx_map_idx: 0,
+            transfer_retention_days: None,
+            snapshot_retention_days: None,
+            canister_ids: CanisterIds::default(),
+            schema_version: latest_migration_id(), // a fresh canister has nothing to migrate
+            compliance_policy: CompliancePolicy::default(),
         }
     }
 }
 
-impl Storable for StableMercxSettings {
-    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
-        serde_cbor::to_vec(self).unwrap().into()
-    }
-
-    fn from_bytes(bytes: std::borrow::Cow<'_, [u8]>) -> Self {
-        serde_cbor::from_slice(&bytes).unwrap_or_default()
-    }
-
-    const BOUND: Bound = Bound::Unbounded;
-}
\ No newline at end of file
+impl_versioned_cbor!(StableMercxSettings);
@@ -0,0 +1,195 @@
+//! Versioned envelope around every stored record: [ENVELOPE_MARKER, ENVELOPE_TAG, version, payload..].
+//!
+//! Adding a field keeps VERSION, the field just needs #[serde(default)] (CBOR) or to be an Option (Candid).
+//! Renaming, removing or retyping a field bumps VERSION and decode_payload converts the older payloads,
+//! so records written by any earlier build keep decoding.
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+use candid::{decode_one, encode_one, CandidType};
+use ic_stable_structures::storable::Bound;
+use ic_stable_structures::Storable;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+// 0xFF is the CBOR break code and Candid starts with "DIDL", so no record written before the envelope starts with it
+const ENVELOPE_MARKER: u8 = 0xFF;
+const ENVELOPE_TAG: u8 = b'V';
+
+/// Version of records written before the envelope existed
+pub const LEGACY_VERSION: u8 = 0;
+
+pub trait Versioned: Sized {
+    const NAME: &'static str;
+    const VERSION: u8;
+
+    fn encode_payload(&self) -> Vec<u8>;
+
+    /// Decode a payload written as `version`, converting older layouts to the current one
+    fn decode_payload(version: u8, payload: &[u8]) -> Result<Self, String>;
+}
+
+pub fn wrap(version: u8, mut payload: Vec<u8>) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(payload.len() + 3);
+    bytes.extend_from_slice(&[ENVELOPE_MARKER, ENVELOPE_TAG, version]);
+    bytes.append(&mut payload);
+    bytes
+}
+
+/// Schema version and payload of stored bytes, records without envelope are LEGACY_VERSION
+pub fn unwrap(bytes: &[u8]) -> (u8, &[u8]) {
+    match bytes {
+        [ENVELOPE_MARKER, ENVELOPE_TAG, version, payload @ ..] => (*version, payload),
+        _ => (LEGACY_VERSION, bytes),
+    }
+}
+
+pub fn encode<T: Versioned>(value: &T) -> Vec<u8> {
+    wrap(T::VERSION, value.encode_payload())
+}
+
+pub fn decode<T: Versioned>(bytes: &[u8]) -> Result<T, String> {
+    let (version, payload) = unwrap(bytes);
+    if version > T::VERSION {
+        Err(format!(
+            "❌ {} record has schema version {} but this build reads up to {}",
+            T::NAME,
+            version,
+            T::VERSION
+        ))?
+    }
+    T::decode_payload(version, payload).map_err(|e| format!("❌ Failed to decode {} v{}: {}", T::NAME, version, e))
+}
+
+/// from_bytes of the versioned records. A record that does not decode traps, it is never replaced by a default
+pub fn decode_or_trap<T: Versioned>(bytes: &[u8]) -> T {
+    decode(bytes).unwrap_or_else(|e| ic_cdk::trap(&e))
+}
+
+pub fn to_cbor<T: Serialize>(value: &T) -> Vec<u8> {
+    serde_cbor::to_vec(value).unwrap()
+}
+
+pub fn from_cbor<T: DeserializeOwned>(payload: &[u8]) -> Result<T, String> {
+    serde_cbor::from_slice(payload).map_err(|e| e.to_string())
+}
+
+pub fn to_candid<T: CandidType>(value: &T) -> Vec<u8> {
+    encode_one(value).unwrap()
+}
+
+pub fn from_candid<T: CandidType + DeserializeOwned>(payload: &[u8]) -> Result<T, String> {
+    decode_one(payload).map_err(|e| e.to_string())
+}
+
+/// Versioned and Storable impls of a record stored as CBOR at VERSION 1, NAME is the type name.
+/// Bound::Unbounded unless a bound is given. A type whose layout changes writes its own impls instead.
+macro_rules! impl_versioned_cbor {
+    ($type:ident) => {
+        $crate::stable_schema::envelope::impl_versioned_cbor!($type, ic_stable_structures::storable::Bound::Unbounded);
+    };
+    ($type:ident, $bound:expr) => {
+        $crate::stable_schema::envelope::impl_versioned!($type, $bound, to_cbor, from_cbor);
+    };
+}
+
+/// impl_versioned_cbor for a record stored as Candid
+macro_rules! impl_versioned_candid {
+    ($type:ident) => {
+        $crate::stable_schema::envelope::impl_versioned_candid!($type, ic_stable_structures::storable::Bound::Unbounded);
+    };
+    ($type:ident, $bound:expr) => {
+        $crate::stable_schema::envelope::impl_versioned!($type, $bound, to_candid, from_candid);
+    };
+}
+
+macro_rules! impl_versioned {
+    ($type:ident, $bound:expr, $encode:ident, $decode:ident) => {
+        impl $crate::stable_schema::envelope::Versioned for $type {
+            const NAME: &'static str = stringify!($type);
+            const VERSION: u8 = 1;
+
+            fn encode_payload(&self) -> Vec<u8> {
+                $crate::stable_schema::envelope::$encode(self)
+            }
+
+            fn decode_payload(_version: u8, payload: &[u8]) -> Result<Self, String> {
+                $crate::stable_schema::envelope::$decode(payload)
+            }
+        }
+
+        impl ic_stable_structures::Storable for $type {
+            fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+                $crate::stable_schema::envelope::encode(self).into()
+            }
+
+            fn from_bytes(bytes: std::borrow::Cow<'_, [u8]>) -> Self {
+                $crate::stable_schema::envelope::decode_or_trap(&bytes)
+            }
+
+            const BOUND: ic_stable_structures::storable::Bound = $bound;
+        }
+    };
+}
+
+pub(crate) use {impl_versioned, impl_versioned_candid, impl_versioned_cbor};
+
+/// Undecoded bytes of a stored V, to inspect records without trapping on the ones that do not decode
+pub struct RawRecord<V> {
+    pub bytes: Vec<u8>,
+    _value: PhantomData<V>,
+}
+
+impl<V> Default for RawRecord<V> {
+    fn default() -> Self {
+        RawRecord { bytes: Vec::new(), _value: PhantomData }
+    }
+}
+
+impl<V: Storable> Storable for RawRecord<V> {
+    fn to_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self.bytes)
+    }
+
+    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
+        RawRecord { bytes: bytes.into_owned(), _value: PhantomData }
+    }
+
+    // same bound as V, a bounded map refuses to load with a larger one
+    const BOUND: Bound = V::BOUND;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Legacy {
+        a: u32,
+    }
+
+    #[derive(Serialize, Deserialize, Debug, PartialEq)]
+    struct Record {
+        a: u32,
+        #[serde(default)]
+        b: Option<String>,
+    }
+
+    impl_versioned_cbor!(Record);
+
+    #[test]
+    fn legacy_records_decode_and_new_ones_carry_their_version() {
+        let legacy = to_cbor(&Legacy { a: 7 });
+        assert_eq!(unwrap(&legacy).0, LEGACY_VERSION);
+        assert_eq!(decode::<Record>(&legacy).unwrap(), Record { a: 7, b: None });
+
+        let record = Record { a: 1, b: Some("x".to_string()) };
+        let bytes = encode(&record);
+        assert_eq!(unwrap(&bytes).0, 1);
+        assert_eq!(decode::<Record>(&bytes).unwrap(), record);
+
+        let future = wrap(2, to_cbor(&record));
+        assert!(decode::<Record>(&future).unwrap_err().contains("schema version 2"));
+    }
+}
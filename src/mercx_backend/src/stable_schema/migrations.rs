@@ -0,0 +1,74 @@
+use std::cell::RefCell;
+
+use ic_stable_structures::{Memory, StableBTreeMap, Storable};
+
+use crate::audit::audit_log;
+use crate::stable_memory::{LPMETADATA, LP_TOKEN_MAP, POOLS, TOKENS, USER_BALANCES};
+use crate::stable_mercx_settings::mercx_settings_map;
+use crate::transfers::handlers::{migrate_transfer_links, reindex_transfers};
+
+/// One-off data migration, run once by post_upgrade in id order.
+/// StableMercxSettings.schema_version holds the id of the last one applied
+pub struct Migration {
+    pub id: u32,
+    pub name: &'static str,
+    pub run: fn(),
+}
+
+// append only, ids are never reused or reordered
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        id: 1,
+        name: "link_legacy_transfers",
+        run: migrate_transfer_links,
+    },
+    Migration {
+        id: 2,
+        name: "envelope_config_records",
+        run: envelope_config_records,
+    },
+    // the principal index was keyed by the calling principal, admin payouts were filed under the admin
+    Migration {
+        id: 3,
+        name: "key_transfer_principal_index_by_account",
+        run: reindex_transfers,
+    },
+];
+
+pub fn latest_migration_id() -> u32 {
+    MIGRATIONS.last().map(|migration| migration.id).unwrap_or(0)
+}
+
+pub fn pending_migrations() -> Vec<&'static Migration> {
+    let applied = mercx_settings_map::get().schema_version;
+    MIGRATIONS.iter().filter(|migration| migration.id > applied).collect()
+}
+
+/// Apply the pending migrations. A trap rolls the whole upgrade back, so a half applied migration is never kept
+pub fn run_migrations() {
+    for migration in pending_migrations() {
+        ic_cdk::println!("Running stable memory migration {} {}", migration.id, migration.name);
+        (migration.run)();
+        mercx_settings_map::set_schema_version(migration.id);
+        audit_log::record(ic_cdk::caller(), "migration", format!("{} {}", migration.id, migration.name));
+    }
+}
+
+/// Write every record back so it is stored with the current envelope
+fn rewrite<K: Storable + Ord + Clone, V: Storable, M: Memory>(map: &RefCell<StableBTreeMap<K, V, M>>) {
+    let mut map = map.borrow_mut();
+    let entries: Vec<(K, V)> = map.iter().collect();
+    for (key, value) in entries {
+        map.insert(key, value);
+    }
+}
+
+// the small maps holding configuration. Settings are rewritten by set_schema_version,
+// the append only logs keep their legacy records which still decode as version 0
+fn envelope_config_records() {
+    TOKENS.with(rewrite);
+    POOLS.with(rewrite);
+    LPMETADATA.with(rewrite);
+    LP_TOKEN_MAP.with(rewrite);
+    USER_BALANCES.with(rewrite);
+}
@@ -0,0 +1,4 @@
+pub mod envelope;
+pub mod migrations;
+pub mod schema_reply;
+pub mod verify;
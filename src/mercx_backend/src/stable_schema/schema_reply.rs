@@ -0,0 +1,21 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+/// Decode check of every record of one stable map
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct StoreSchemaReport {
+    pub store: String,
+    pub version: u8, // version this build writes
+    pub records: u64,
+    pub legacy_records: u64, // written before the envelope, still decoded as version 0
+    pub failed_records: u64,
+    pub first_error: Option<String>,
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct SchemaReport {
+    pub schema_version: u32, // last migration applied
+    pub pending_migrations: Vec<String>,
+    pub stores: Vec<StoreSchemaReport>,
+    pub ok: bool, // every record decodes
+}
@@ -0,0 +1,99 @@
+use candid::Principal;
+use ic_stable_structures::memory_manager::MemoryId;
+use ic_stable_structures::Storable;
+
+use super::envelope::{self, Versioned, LEGACY_VERSION};
+use super::migrations::pending_migrations;
+use super::schema_reply::{SchemaReport, StoreSchemaReport};
+use crate::audit::stable_audit_entry::{StableAuditEntry, StableAuditId};
+use crate::balances::stable_balance::{StableBalance, StableBalanceId};
+use crate::balances::stable_pending_deposit::StablePendingDeposit;
+use crate::balances::stable_pending_withdrawal::StablePendingWithdrawal;
+use crate::block_log::stable_block::{StableBlock, StableBlockId};
+use crate::circuit_breaker::stable_circuit_breaker::StableCircuitBreaker;
+use crate::kyc::stable_kyc_user::StableKycUser;
+use crate::lp_metadata::stable_lp_metadata::{LPToken, StableLpMetadataId};
+use crate::pool::stable_pool::{StablePool, StablePoolId};
+use crate::pool_analytics::stable_analytics::{PoolAnalyticsId, PoolSnapshot, PoolSnapshotKey, StablePoolTimeSeries};
+use crate::rbac::guards::is_admin;
+use crate::stable_lp_token::stable_lp_token::{StableLPToken, StableLPTokenId};
+use crate::stable_memory::{self, *};
+use crate::stable_mercx_settings::mercx_settings_map;
+use crate::stable_mercx_settings::stable_mercx_settings::StableMercxSettings;
+use crate::stable_tx::stable_tx::{StableTx, StableTxId};
+use crate::token::stable_token::{StableToken, StableTokenId};
+use crate::trading_hours::stable_trading_schedule::StableTradingSchedule;
+use crate::transfers::stable_transfer::{StableTransfer, StableTransferId};
+
+fn check_record<V: Versioned>(report: &mut StoreSchemaReport, bytes: &[u8]) {
+    report.records += 1;
+    if envelope::unwrap(bytes).0 == LEGACY_VERSION {
+        report.legacy_records += 1;
+    }
+    if let Err(e) = envelope::decode::<V>(bytes) {
+        report.failed_records += 1;
+        report.first_error.get_or_insert(e);
+    }
+}
+
+fn new_report<V: Versioned>(store: &str) -> StoreSchemaReport {
+    StoreSchemaReport {
+        store: store.to_string(),
+        version: V::VERSION,
+        records: 0,
+        legacy_records: 0,
+        failed_records: 0,
+        first_error: None,
+    }
+}
+
+fn check_map<K: Storable + Ord + Clone, V: Storable + Versioned>(store: &str, memory_id: MemoryId) -> StoreSchemaReport {
+    let mut report = new_report::<V>(store);
+    stable_memory::with_raw_map::<K, V, _>(memory_id, |map| {
+        for (_, record) in map.iter() {
+            check_record::<V>(&mut report, &record.bytes);
+        }
+    });
+    report
+}
+
+fn check_cell<V: Storable + Versioned>(store: &str, memory_id: MemoryId) -> StoreSchemaReport {
+    let mut report = new_report::<V>(store);
+    check_record::<V>(&mut report, &stable_memory::raw_cell_bytes::<V>(memory_id));
+    report
+}
+
+/// Dry run of the stored data: decodes every record of every map without trapping on the ones that fail
+#[ic_cdk::query(guard = "is_admin")]
+fn verify_stable_schema() -> SchemaReport {
+    let stores = vec![
+        check_cell::<StableMercxSettings>("MERCX_SETTINGS", MERCX_SETTINGS_MEMORY_ID),
+        check_cell::<StableCircuitBreaker>("CIRCUIT_BREAKER", CIRCUIT_BREAKER_MEMORY_ID),
+        check_map::<StablePoolId, StablePool>("POOLS", POOL_MEMORY_ID),
+        check_map::<StableTokenId, StableToken>("TOKENS", TOKEN_MEMORY_ID),
+        check_map::<StableTransferId, StableTransfer>("TRANSFERS", TRANSFER_MEMORY_ID),
+        check_map::<StableTransferId, StableTransfer>("TRANSFER_ARCHIVE", TRANSFER_ARCHIVE_MEMORY_ID),
+        check_map::<StableLpMetadataId, LPToken>("LPMETADATA", LP_METADATA_MEM_ID),
+        check_map::<StableLPTokenId, StableLPToken>("LP_TOKEN_MAP", LP_TOKEN_MEMORY_ID),
+        check_map::<PoolAnalyticsId, StablePoolTimeSeries>("ANALYTICS_DATA", ANALYTICS_MEMORY_ID),
+        check_map::<PoolSnapshotKey, PoolSnapshot>("SNAPSHOT_ARCHIVE", SNAPSHOT_ARCHIVE_MEMORY_ID),
+        check_map::<StableBalanceId, StableBalance>("USER_BALANCES", USER_BALANCE_MEMORY_ID),
+        check_map::<StableBalanceId, StablePendingWithdrawal>("PENDING_WITHDRAWALS", PENDING_WITHDRAWAL_MEMORY_ID),
+        check_map::<StableBalanceId, StablePendingDeposit>("PENDING_DEPOSITS", PENDING_DEPOSIT_MEMORY_ID),
+        check_map::<StableTxId, StableTx>("TX_MAP", TX_MEMORY_ID),
+        check_map::<StableBlockId, StableBlock>("BLOCKS", BLOCK_MEMORY_ID),
+        check_map::<StableAuditId, StableAuditEntry>("AUDIT_LOG", AUDIT_LOG_MEMORY_ID),
+        check_map::<Principal, StableKycUser>("KYC_CACHE", KYC_CACHE_MEMORY_ID),
+        check_map::<StablePoolId, StableTradingSchedule>("POOL_SCHEDULES", POOL_SCHEDULE_MEMORY_ID),
+    ];
+
+    SchemaReport {
+        schema_version: mercx_settings_map::get().schema_version,
+        pending_migrations: pending_migrations()
+            .iter()
+            .map(|migration| format!("{} {}", migration.id, migration.name))
+            .collect(),
+        ok: stores.iter().all(|store| store.failed_records == 0),
+        stores,
+    }
+}
@@ -0,0 +1,55 @@
+use candid::{CandidType, Nat, Principal};
+use serde::{Deserialize, Serialize};
+
+use crate::helpers::math_helpers::nat_to_decimals_f64;
+use crate::StableToken;
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct AddLiquidityTx {
+    pub tx_id: u64,
+    pub user: Principal,
+    pub pool_id: u32,
+    pub amount_0: Nat, // amounts added to the pool, excess refunds are not included
+    pub amount_1: Nat,
+    pub add_lp_token_amount: Nat,
+    pub price: f64, // token_1 per token_0 the liquidity was added at
+    pub transfer_ids: Vec<u64>,
+    pub ts: u64,
+    #[serde(default)]
+    pub usd_value: Option<f64>, // USD value at execution, set shortly after the tx is recorded
+}
+
+impl AddLiquidityTx {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_success(
+        user: Principal,
+        pool_id: u32,
+        token_0: &StableToken,
+        amount_0: &Nat,
+        token_1: &StableToken,
+        amount_1: &Nat,
+        add_lp_token_amount: &Nat,
+        transfer_ids: &[u64],
+        ts: u64,
+    ) -> Self {
+        let price = match (
+            nat_to_decimals_f64(token_0.decimals(), amount_0),
+            nat_to_decimals_f64(token_1.decimals(), amount_1),
+        ) {
+            (Some(amount_0), Some(amount_1)) if amount_0 > 0.0 => amount_1 / amount_0,
+            _ => 0.0,
+        };
+        Self {
+            tx_id: 0,
+            user,
+            pool_id,
+            amount_0: amount_0.clone(),
+            amount_1: amount_1.clone(),
+            add_lp_token_amount: add_lp_token_amount.clone(),
+            price,
+            transfer_ids: transfer_ids.to_vec(),
+            ts,
+            usd_value: None,
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use candid::{CandidType, Nat, Principal};
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct AddPoolTx {
+    pub tx_id: u64,
+    pub user: Principal,
+    pub pool_id: u32,
+    pub token_id_0: u32,
+    pub amount_0: Nat,
+    pub token_id_1: u32,
+    pub amount_1: Nat,
+    pub add_lp_token_amount: Nat,
+    pub lp_fee_bps: u8,
+    pub transfer_ids: Vec<u64>,
+    pub ts: u64,
+    #[serde(default)]
+    pub usd_value: Option<f64>, // USD value at execution, set shortly after the tx is recorded
+}
@@ -0,0 +1,8 @@
+#[allow(clippy::module_inception)]
+pub mod stable_tx;
+pub mod stable_tx_index;
+pub mod swap_tx;
+pub mod add_pool_tx;
+pub mod add_liquidity_tx;
+pub mod remove_liquidity_tx;
+pub mod tx_map;
@@ -0,0 +1,18 @@
+use candid::{CandidType, Nat, Principal};
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct RemoveLiquidityTx {
+    pub tx_id: u64,
+    pub user: Principal,
+    pub pool_id: u32,
+    pub amount_0: Nat,
+    pub lp_fee_0: Nat, // accrued lp fees paid out with amount_0
+    pub amount_1: Nat,
+    pub lp_fee_1: Nat,
+    pub remove_lp_token_amount: Nat,
+    pub transfer_ids: Vec<u64>,
+    pub ts: u64,
+    #[serde(default)]
+    pub usd_value: Option<f64>, // USD value at execution, set shortly after the tx is recorded
+}
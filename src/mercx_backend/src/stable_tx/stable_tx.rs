@@ -0,0 +1,155 @@
+use candid::{CandidType, Nat, Principal};
+use ic_stable_structures::{storable::Bound, Storable};
+use serde::{Deserialize, Serialize};
+
+use super::add_liquidity_tx::AddLiquidityTx;
+use super::add_pool_tx::AddPoolTx;
+use super::remove_liquidity_tx::RemoveLiquidityTx;
+use super::swap_tx::SwapTx;
+use crate::helpers::math_helpers::nat_add;
+use crate::pool::handlers as pool_handlers;
+use crate::stable_schema::envelope::impl_versioned_cbor;
+
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct StableTxId(pub u64);
+
+impl Storable for StableTxId {
+    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
+        serde_cbor::to_vec(self).unwrap().into()
+    }
+
+    fn from_bytes(bytes: std::borrow::Cow<'_, [u8]>) -> Self {
+        serde_cbor::from_slice(&bytes).unwrap()
+    }
+
+    const BOUND: Bound = Bound::Unbounded;
+}
+
+/// (token_id, amount) pairs
+pub type TokenAmounts = Vec<(u32, Nat)>;
+
+/// One completed swap or liquidity operation. The ledger side of it is in the linked transfer_ids.
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub enum StableTx {
+    Swap(SwapTx),
+    AddPool(AddPoolTx),
+    AddLiquidity(AddLiquidityTx),
+    RemoveLiquidity(RemoveLiquidityTx),
+}
+
+impl StableTx {
+    pub fn tx_id(&self) -> u64 {
+        match self {
+            StableTx::Swap(tx) => tx.tx_id,
+            StableTx::AddPool(tx) => tx.tx_id,
+            StableTx::AddLiquidity(tx) => tx.tx_id,
+            StableTx::RemoveLiquidity(tx) => tx.tx_id,
+        }
+    }
+
+    pub fn set_tx_id(&mut self, tx_id: u64) {
+        match self {
+            StableTx::Swap(tx) => tx.tx_id = tx_id,
+            StableTx::AddPool(tx) => tx.tx_id = tx_id,
+            StableTx::AddLiquidity(tx) => tx.tx_id = tx_id,
+            StableTx::RemoveLiquidity(tx) => tx.tx_id = tx_id,
+        }
+    }
+
+    pub fn user(&self) -> Principal {
+        match self {
+            StableTx::Swap(tx) => tx.user,
+            StableTx::AddPool(tx) => tx.user,
+            StableTx::AddLiquidity(tx) => tx.user,
+            StableTx::RemoveLiquidity(tx) => tx.user,
+        }
+    }
+
+    pub fn set_user(&mut self, user: Principal) {
+        match self {
+            StableTx::Swap(tx) => tx.user = user,
+            StableTx::AddPool(tx) => tx.user = user,
+            StableTx::AddLiquidity(tx) => tx.user = user,
+            StableTx::RemoveLiquidity(tx) => tx.user = user,
+        }
+    }
+
+    pub fn pool_ids(&self) -> Vec<u32> {
+        match self {
+            StableTx::Swap(tx) => tx.pool_ids(),
+            StableTx::AddPool(tx) => vec![tx.pool_id],
+            StableTx::AddLiquidity(tx) => vec![tx.pool_id],
+            StableTx::RemoveLiquidity(tx) => vec![tx.pool_id],
+        }
+    }
+
+    pub fn ts(&self) -> u64 {
+        match self {
+            StableTx::Swap(tx) => tx.ts,
+            StableTx::AddPool(tx) => tx.ts,
+            StableTx::AddLiquidity(tx) => tx.ts,
+            StableTx::RemoveLiquidity(tx) => tx.ts,
+        }
+    }
+
+    pub fn transfer_ids(&self) -> &[u64] {
+        match self {
+            StableTx::Swap(tx) => &tx.transfer_ids,
+            StableTx::AddPool(tx) => &tx.transfer_ids,
+            StableTx::AddLiquidity(tx) => &tx.transfer_ids,
+            StableTx::RemoveLiquidity(tx) => &tx.transfer_ids,
+        }
+    }
+
+    pub fn usd_value(&self) -> Option<f64> {
+        match self {
+            StableTx::Swap(tx) => tx.usd_value,
+            StableTx::AddPool(tx) => tx.usd_value,
+            StableTx::AddLiquidity(tx) => tx.usd_value,
+            StableTx::RemoveLiquidity(tx) => tx.usd_value,
+        }
+    }
+
+    pub fn set_usd_value(&mut self, usd_value: f64) {
+        match self {
+            StableTx::Swap(tx) => tx.usd_value = Some(usd_value),
+            StableTx::AddPool(tx) => tx.usd_value = Some(usd_value),
+            StableTx::AddLiquidity(tx) => tx.usd_value = Some(usd_value),
+            StableTx::RemoveLiquidity(tx) => tx.usd_value = Some(usd_value),
+        }
+    }
+
+    /// What the user paid in and received, from the user's perspective
+    pub fn token_amounts(&self) -> (TokenAmounts, TokenAmounts) {
+        match self {
+            StableTx::Swap(tx) => (
+                vec![(tx.pay_token_id, tx.pay_amount.clone())],
+                vec![(tx.receive_token_id, tx.receive_amount.clone())],
+            ),
+            StableTx::AddPool(tx) => (
+                vec![(tx.token_id_0, tx.amount_0.clone()), (tx.token_id_1, tx.amount_1.clone())],
+                Vec::new(),
+            ),
+            // pool tokens never change, so the pool gives the token ids
+            StableTx::AddLiquidity(tx) => match pool_handlers::get_by_pool_id(tx.pool_id) {
+                Some(pool) => (
+                    vec![(pool.token_id_0, tx.amount_0.clone()), (pool.token_id_1, tx.amount_1.clone())],
+                    Vec::new(),
+                ),
+                None => (Vec::new(), Vec::new()),
+            },
+            StableTx::RemoveLiquidity(tx) => match pool_handlers::get_by_pool_id(tx.pool_id) {
+                Some(pool) => (
+                    Vec::new(),
+                    vec![
+                        (pool.token_id_0, nat_add(&tx.amount_0, &tx.lp_fee_0)),
+                        (pool.token_id_1, nat_add(&tx.amount_1, &tx.lp_fee_1)),
+                    ],
+                ),
+                None => (Vec::new(), Vec::new()),
+            },
+        }
+    }
+}
+
+impl_versioned_cbor!(StableTx);
@@ -0,0 +1,59 @@
+use candid::{CandidType, Nat, Principal};
+use serde::{Deserialize, Serialize};
+
+use crate::swap::swap_calc::SwapCalc;
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct SwapTx {
+    pub tx_id: u64,
+    pub user: Principal,
+    pub pay_token_id: u32,
+    pub pay_amount: Nat,
+    pub receive_token_id: u32,
+    pub receive_amount: Nat, // after lp and gas fees
+    pub mid_price: f64,
+    pub price: f64,
+    pub slippage: f64,
+    pub txs: Vec<SwapCalc>, // one per pool hop, with that hop's lp_fee and gas_fee
+    pub transfer_ids: Vec<u64>,
+    pub ts: u64,
+    #[serde(default)]
+    pub usd_value: Option<f64>, // USD value at execution, set shortly after the tx is recorded
+}
+
+impl SwapTx {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new_success(
+        user: Principal,
+        pay_token_id: u32,
+        pay_amount: &Nat,
+        receive_token_id: u32,
+        receive_amount: &Nat,
+        mid_price: f64,
+        price: f64,
+        slippage: f64,
+        txs: &[SwapCalc],
+        transfer_ids: &[u64],
+        ts: u64,
+    ) -> Self {
+        Self {
+            tx_id: 0,
+            user,
+            pay_token_id,
+            pay_amount: pay_amount.clone(),
+            receive_token_id,
+            receive_amount: receive_amount.clone(),
+            mid_price,
+            price,
+            slippage,
+            txs: txs.to_vec(),
+            transfer_ids: transfer_ids.to_vec(),
+            ts,
+            usd_value: None,
+        }
+    }
+
+    pub fn pool_ids(&self) -> Vec<u32> {
+        self.txs.iter().map(|swap_calc| swap_calc.pool_id).collect()
+    }
+}
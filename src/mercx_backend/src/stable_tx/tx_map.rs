@@ -0,0 +1,253 @@
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+use super::stable_tx::{StableTx, StableTxId};
+use super::stable_tx_index::{StableTxPoolKey, StableTxTsKey, StableTxUserKey};
+use crate::stable_memory::{TX_MAP, TX_POOL_INDEX, TX_TS_INDEX, TX_USER_INDEX};
+use crate::stable_mercx_settings::mercx_settings_map;
+use crate::pool_analytics::analytics::token_amount_to_usd;
+use crate::token::handlers::get_by_token_id;
+use crate::block_log::dex_block;
+use crate::rbac::role::Role;
+use crate::rbac::roles_map::has_role;
+
+const DEFAULT_TXS_LIMIT: u32 = 50;
+const MAX_TXS_LIMIT: u32 = 100;
+
+/// One page of transactions, newest first.
+/// Pass next_cursor back as cursor to get the next (older) page, None means there are no more.
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct TxPage {
+    pub txs: Vec<StableTx>,
+    pub next_cursor: Option<u64>,
+}
+
+pub fn get_by_tx_id(tx_id: u64) -> Option<StableTx> {
+    TX_MAP.with(|m| m.borrow().get(&StableTxId(tx_id)))
+}
+
+/// Store a transaction with a new tx_id and index it by user, pool and time. Returns the tx_id.
+pub fn insert(tx: &StableTx) -> u64 {
+    let tx_id = mercx_settings_map::inc_tx_map_idx();
+    let mut insert_tx = tx.clone();
+    insert_tx.set_tx_id(tx_id);
+
+    TX_MAP.with(|m| m.borrow_mut().insert(StableTxId(tx_id), insert_tx.clone()));
+    TX_USER_INDEX.with(|m| m.borrow_mut().insert(StableTxUserKey(insert_tx.user(), tx_id), ()));
+    TX_POOL_INDEX.with(|m| {
+        let mut index = m.borrow_mut();
+        for pool_id in insert_tx.pool_ids() {
+            index.insert(StableTxPoolKey(pool_id, tx_id), ());
+        }
+    });
+    TX_TS_INDEX.with(|m| m.borrow_mut().insert(StableTxTsKey(insert_tx.ts(), tx_id), ()));
+    tx_id
+}
+
+/// insert(), append it to the block log and price the tx in the background, the USD value is stored once the price lookup returns
+pub fn record(tx: &StableTx) -> u64 {
+    let tx_id = insert(tx);
+    if let Some(tx) = get_by_tx_id(tx_id) {
+        dex_block::log_tx(&tx);
+    }
+    ic_cdk::spawn(update_usd_value(tx_id));
+    tx_id
+}
+
+/// USD value of what the user paid in, or of what they received for liquidity removals.
+/// Left None when any token has no price, so history shows it as unknown rather than $0
+async fn update_usd_value(tx_id: u64) {
+    let Some(tx) = get_by_tx_id(tx_id) else {
+        return;
+    };
+    let (paid, received) = tx.token_amounts();
+    let amounts = if paid.is_empty() { received } else { paid };
+
+    let mut usd_value = 0.0;
+    for (token_id, amount) in amounts {
+        let Some(token) = get_by_token_id(token_id) else {
+            return;
+        };
+        match token_amount_to_usd(&amount, &token).await {
+            Ok(value) if value > 0.0 => usd_value += value,
+            // 0.0 means no pool or rate source knows the token
+            Ok(_) => return,
+            Err(e) => {
+                ic_cdk::println!("❌ Tx #{} USD value not set: {}", tx_id, e);
+                return;
+            }
+        }
+    }
+
+    TX_MAP.with(|m| {
+        let mut map = m.borrow_mut();
+        if let Some(mut tx) = map.get(&StableTxId(tx_id)) {
+            tx.set_usd_value(usd_value);
+            map.insert(StableTxId(tx_id), tx);
+        }
+    });
+}
+
+fn page_limit(limit: Option<u32>) -> usize {
+    limit.unwrap_or(DEFAULT_TXS_LIMIT).clamp(1, MAX_TXS_LIMIT) as usize
+}
+
+/// Load the txs for tx_ids, newest first. tx_ids holds up to limit + 1 ids, the extra one only
+/// tells whether there is a next page.
+fn to_tx_page(mut tx_ids: Vec<u64>, limit: usize) -> TxPage {
+    let next_cursor = if tx_ids.len() > limit {
+        tx_ids.truncate(limit);
+        tx_ids.last().copied()
+    } else {
+        None
+    };
+    TxPage {
+        txs: tx_ids.into_iter().filter_map(get_by_tx_id).collect(),
+        next_cursor,
+    }
+}
+
+/// Up to max tx_ids of a user older than cursor, newest first
+pub fn get_tx_ids_by_user(user: Principal, cursor: Option<u64>, max: usize) -> Vec<u64> {
+    let end = StableTxUserKey(user, cursor.unwrap_or(u64::MAX));
+    TX_USER_INDEX.with(|m| {
+        m.borrow()
+            .range(StableTxUserKey(user, 0)..end)
+            .rev()
+            .take(max)
+            .map(|(key, _)| key.1)
+            .collect()
+    })
+}
+
+pub fn get_by_user(user: Principal, cursor: Option<u64>, limit: Option<u32>) -> TxPage {
+    let limit = page_limit(limit);
+    to_tx_page(get_tx_ids_by_user(user, cursor, limit + 1), limit)
+}
+
+pub fn get_by_pool(pool_id: u32, cursor: Option<u64>, limit: Option<u32>) -> TxPage {
+    let limit = page_limit(limit);
+    let end = StableTxPoolKey(pool_id, cursor.unwrap_or(u64::MAX));
+    let tx_ids = TX_POOL_INDEX.with(|m| {
+        m.borrow()
+            .range(StableTxPoolKey(pool_id, 0)..end)
+            .rev()
+            .take(limit + 1)
+            .map(|(key, _)| key.1)
+            .collect()
+    });
+    to_tx_page(tx_ids, limit)
+}
+
+/// Transactions with from_ts <= ts < to_ts
+pub fn get_by_time_range(from_ts: u64, to_ts: u64, cursor: Option<u64>, limit: Option<u32>) -> Result<TxPage, String> {
+    let limit = page_limit(limit);
+    let end = match cursor {
+        Some(cursor) => {
+            let cursor_tx = get_by_tx_id(cursor).ok_or(format!("Invalid cursor {}", cursor))?;
+            StableTxTsKey(cursor_tx.ts(), cursor).min(StableTxTsKey(to_ts, 0))
+        }
+        None => StableTxTsKey(to_ts, 0),
+    };
+    let start = StableTxTsKey(from_ts, 0);
+    if start >= end {
+        return Ok(TxPage { txs: Vec::new(), next_cursor: None });
+    }
+    let tx_ids = TX_TS_INDEX.with(|m| {
+        m.borrow()
+            .range(start..end)
+            .rev()
+            .take(limit + 1)
+            .map(|(key, _)| key.1)
+            .collect()
+    });
+    Ok(to_tx_page(tx_ids, limit))
+}
+
+fn can_read_user(caller: Principal, user: Principal) -> bool {
+    caller == user || has_role(caller, Role::Compliance)
+}
+
+/// Hides the user of every tx the caller may not read, the same rule get_txs_by_user applies
+fn redact_users(mut page: TxPage) -> TxPage {
+    let caller = ic_cdk::caller();
+    for tx in &mut page.txs {
+        if !can_read_user(caller, tx.user()) {
+            tx.set_user(Principal::anonymous());
+        }
+    }
+    page
+}
+
+/// Transactions of a user. Only the user and the Compliance role can read them.
+#[ic_cdk::query]
+pub fn get_txs_by_user(user: Principal, cursor: Option<u64>, limit: Option<u32>) -> Result<TxPage, String> {
+    if !can_read_user(ic_cdk::caller(), user) {
+        Err("❌ Only the user or compliance can read a user's transactions")?
+    }
+    Ok(get_by_user(user, cursor, limit))
+}
+
+/// Transactions of a pool, the user is anonymous unless the caller may read it
+#[ic_cdk::query]
+pub fn get_txs_by_pool(pool_id: u32, cursor: Option<u64>, limit: Option<u32>) -> TxPage {
+    redact_users(get_by_pool(pool_id, cursor, limit))
+}
+
+/// Transactions with from_ts <= ts < to_ts, the user is anonymous unless the caller may read it
+#[ic_cdk::query]
+pub fn get_txs_by_time(from_ts: u64, to_ts: u64, cursor: Option<u64>, limit: Option<u32>) -> Result<TxPage, String> {
+    get_by_time_range(from_ts, to_ts, cursor, limit).map(redact_users)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stable_tx::add_pool_tx::AddPoolTx;
+    use candid::Nat;
+
+    fn add_pool_tx(user: Principal, pool_id: u32, ts: u64) -> StableTx {
+        StableTx::AddPool(AddPoolTx {
+            tx_id: 0,
+            user,
+            pool_id,
+            token_id_0: 1,
+            amount_0: Nat::from(1_u32),
+            token_id_1: 2,
+            amount_1: Nat::from(1_u32),
+            add_lp_token_amount: Nat::from(1_u32),
+            lp_fee_bps: 30,
+            transfer_ids: Vec::new(),
+            ts,
+            usd_value: None,
+        })
+    }
+
+    #[test]
+    fn pages_are_newest_first_and_cursor_continues() {
+        let user = Principal::from_slice(&[7]);
+        let other = Principal::from_slice(&[8]);
+        let tx_ids: Vec<u64> = (0..5).map(|i| insert(&add_pool_tx(user, 1, 100 + i))).collect();
+        insert(&add_pool_tx(other, 2, 103));
+
+        let first = get_by_user(user, None, Some(2));
+        assert_eq!(first.txs.iter().map(|tx| tx.tx_id()).collect::<Vec<_>>(), vec![tx_ids[4], tx_ids[3]]);
+        assert_eq!(first.next_cursor, Some(tx_ids[3]));
+
+        let second = get_by_user(user, first.next_cursor, Some(2));
+        assert_eq!(second.txs.iter().map(|tx| tx.tx_id()).collect::<Vec<_>>(), vec![tx_ids[2], tx_ids[1]]);
+
+        let last = get_by_user(user, second.next_cursor, Some(2));
+        assert_eq!(last.txs.iter().map(|tx| tx.tx_id()).collect::<Vec<_>>(), vec![tx_ids[0]]);
+        assert_eq!(last.next_cursor, None);
+
+        assert_eq!(get_by_pool(2, None, None).txs.len(), 1);
+
+        // ts 101..=103 holds tx 1, 2, 3 of the user and the other user's tx
+        let by_time = get_by_time_range(101, 104, None, Some(3)).unwrap();
+        assert_eq!(by_time.txs.len(), 3);
+        let rest = get_by_time_range(101, 104, by_time.next_cursor, Some(3)).unwrap();
+        assert_eq!(rest.txs.iter().map(|tx| tx.tx_id()).collect::<Vec<_>>(), vec![tx_ids[1]]);
+        assert_eq!(rest.next_cursor, None);
+    }
+}
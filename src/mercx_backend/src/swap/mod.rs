@@ -11,3 +11,4 @@ pub mod send_receive_token;
 pub mod return_pay_token;
 pub mod swap_transfer_from;
 pub mod swap_transfer;
+pub mod swap_balance;
@@ -38,6 +38,10 @@ pub async fn return_pay_token(
                 tx_id: TxId::BlockIndex(tx_id),
                 transfer_type: TransferType::Transfer,       // This is a SWAP, not liquidity removal
                 ts,
+                account: Some(*to_principal_id),
+                pool_ids: Vec::new(),
+                request_id: None,
+                usd_price: None,
             });
             transfer_ids.push(transfer_id);
            // request_map::update_status(request_id, StatusCode::ReturnPayTokenSuccess, None);
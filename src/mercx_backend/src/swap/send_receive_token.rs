@@ -11,8 +11,9 @@ use crate::ic::{
     transfer::{icp_transfer, icrc1_transfer},
 };
 use crate::token::{stable_token::StableToken};
+use crate::compliance::rules;
 use crate::transfers::{stable_transfer::{StableTransfer,TransferType}, handlers, tx_id::TxId};
-//use crate::stable_tx::{stable_tx::StableTx, swap_tx::SwapTx, tx_map};
+use crate::stable_tx::{stable_tx::StableTx, swap_tx::SwapTx, tx_map};
 
 pub async fn send_receive_token(
    // request_id: u64,
@@ -52,8 +53,18 @@ pub async fn send_receive_token(
                 tx_id: TxId::BlockIndex(tx_id),
                 transfer_type: TransferType::Swap,       // This is a SWAP, not liquidity removal
                 ts,
+                account: match to_address {
+                    Address::PrincipalId(to_principal_id) => Some(*to_principal_id),
+                    Address::AccountId(_) => None,
+                },
+                pool_ids: Vec::new(),
+                request_id: None,
+                usd_price: None,
             });
             transfer_ids.push(transfer_id);
+            if let Address::PrincipalId(to_principal_id) = to_address {
+                rules::record_purchase(receive_token, to_principal_id.owner, ts);
+            }
            // request_map::update_status(request_id, StatusCode::SendReceiveTokenSuccess, None);
         }
         Err(e) => {
@@ -76,22 +87,20 @@ pub async fn send_receive_token(
         }
     }
 
-    // let swap_tx = SwapTx::new_success(
-    //     user_id,
-    //   //  request_id,
-    //     pay_token_id,
-    //     pay_amount,
-    //     receive_token_id,
-    //     receive_amount,
-    //     mid_price,
-    //     price,
-    //     slippage,
-    //     txs,
-    //     transfer_ids,
-    //  //   &claim_ids,
-    //     ts,
-    // );
-  //  let tx_id = tx_map::insert(&StableTx::Swap(swap_tx.clone()));
+    let swap_tx = SwapTx::new_success(
+        ic_cdk::caller(),
+        pay_token.token_id(),
+        pay_amount,
+        receive_token_id,
+        receive_amount,
+        mid_price,
+        price,
+        slippage,
+        txs,
+        transfer_ids,
+        ts,
+    );
+    tx_map::record(&StableTx::Swap(swap_tx));
   let reply = match (pay_token.canister_id(), receive_token.canister_id()) {
     (Some(pay_addr), Some(receive_addr)) => SwapReply {
       //  tx_id: 0, // or any dummy/default value if you don’t store transactions
@@ -1,14 +1,17 @@
 use ic_cdk::update;
 
+use crate::circuit_breaker::breaker::swaps_open;
+
 use super::swap_args::SwapArgs;
 use super::swap_reply::SwapReply;
 use super::swap_transfer::{swap_transfer};
 use super::swap_transfer_from::{swap_transfer_from};
+use super::swap_balance::swap_balance;
 
 
 /// Pay and Receive are from the user's perspective
 /// Swap tokens
-#[update]
+#[update(guard = "swaps_open")]
 pub async fn swap_tokens(args: SwapArgs) -> Result<SwapReply, String> {
     // determine if using icrc2_approve+icrc2_transfer_from or icrc1_transfer method
     match args.pay_tx_id {
@@ -17,3 +20,9 @@ pub async fn swap_tokens(args: SwapArgs) -> Result<SwapReply, String> {
     }
 }
 
+
+/// Swap using the caller's internal balance (see notify_deposit and withdraw)
+#[update(guard = "swaps_open")]
+pub async fn swap_with_balance(args: SwapArgs) -> Result<SwapReply, String> {
+    swap_balance(args).await
+}
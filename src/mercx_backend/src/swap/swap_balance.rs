@@ -0,0 +1,106 @@
+use candid::Nat;
+
+use crate::balances::balance_map::{credit, debit};
+use crate::helpers::math_helpers::nat_is_zero;
+use crate::ic::general::get_time;
+use crate::ic::id::caller;
+use crate::compliance::compliance_policy::Operation;
+use crate::compliance::policy as compliance;
+use crate::compliance::rules;
+use crate::ic::address::Address;
+use icrc_ledger_types::icrc1::account::Account;
+use crate::pool_analytics::analytics_storage::record_pool_snapshot2;
+use crate::stable_mercx_settings::mercx_settings_map;
+use crate::swap::calculate_amounts::calculate_amounts;
+use crate::swap::swap_args::SwapArgs;
+use crate::swap::swap_reply::SwapReply;
+use crate::swap::swap_reply_helpers::to_txs;
+use crate::swap::update_liquidity_pool::update_liquidity_pool;
+use crate::token::handlers;
+use crate::StableToken;
+use crate::stable_tx::{stable_tx::StableTx, swap_tx::SwapTx, tx_map};
+
+/// Swap settled against the caller's internal balance, no ledger calls.
+/// pay_amount is debited from and the receive amount credited to the internal balance book.
+pub async fn swap_balance(args: SwapArgs) -> Result<SwapReply, String> {
+    let (pay_token, pay_amount, receive_token, max_slippage, quote) = check_arguments(&args)?;
+    let owner = caller();
+    compliance::check(Operation::Swap, owner, &[&pay_token, &receive_token], None).await?;
+    rules::check_swap(owner, &pay_token, &receive_token, &quote, &Address::PrincipalId(Account::from(owner))).await?;
+    // the compliance and token rule checks are the last awaits before the debit
+    let ts = get_time();
+
+    debit(owner, pay_token.token_id(), &pay_amount, ts)?;
+
+    let (receive_amount, mid_price, price, slippage, swaps) =
+        match update_liquidity_pool(&pay_token, &pay_amount, &receive_token, args.receive_amount.as_ref(), max_slippage) {
+            Ok(result) => result,
+            Err(e) => {
+                credit(owner, pay_token.token_id(), &pay_amount, ts);
+                Err(format!(" failed. {}", e))?
+            }
+        };
+
+    credit(owner, receive_token.token_id(), &receive_amount, ts);
+    rules::record_purchase(&receive_token, owner, ts);
+    tx_map::record(&StableTx::Swap(SwapTx::new_success(
+        owner,
+        pay_token.token_id(),
+        &pay_amount,
+        receive_token.token_id(),
+        &receive_amount,
+        mid_price,
+        price,
+        slippage,
+        &swaps,
+        &[],
+        ts,
+    )));
+
+    for swap_calc in &swaps {
+        let _ = record_pool_snapshot2(swap_calc.pool_id).await;
+    }
+
+    Ok(SwapReply {
+        pay_address: pay_token.canister_id.to_string(),
+        pay_symbol: pay_token.symbol(),
+        pay_amount,
+        receive_address: receive_token.canister_id.to_string(),
+        receive_symbol: receive_token.symbol(),
+        receive_amount,
+        mid_price,
+        price,
+        slippage,
+        txs: to_txs(&swaps, ts),
+        transfer_ids: Vec::new(), // settled internally, no ledger transfers
+        ts,
+    })
+}
+
+fn check_arguments(args: &SwapArgs) -> Result<(StableToken, Nat, StableToken, f64, Nat), String> {
+    let pay_token = handlers::get_by_token(&args.pay_token)?;
+    let receive_token = handlers::get_by_token(&args.receive_token)?;
+    let pay_amount = args.pay_amount.clone();
+
+    if pay_token.is_removed() {
+        Err("Pay token is suspended or removed".to_string())?;
+    }
+    if receive_token.is_removed() {
+        Err("Receive token is suspended or removed".to_string())?;
+    }
+    if nat_is_zero(&pay_amount) {
+        Err("Pay amount is zero".to_string())?;
+    }
+    if args.pay_tx_id.is_some() {
+        Err("Pay tx_id not supported for balance swaps".to_string())?;
+    }
+    // receive amount stays in the internal balance, use withdraw to send it elsewhere
+    if args.receive_address.is_some() {
+        Err("Receive address not supported for balance swaps".to_string())?;
+    }
+
+    let max_slippage = args.max_slippage.unwrap_or(mercx_settings_map::get().default_max_slippage);
+    let (quote, ..) = calculate_amounts(&pay_token, &pay_amount, &receive_token, args.receive_amount.as_ref(), max_slippage)?;
+
+    Ok((pay_token, pay_amount, receive_token, max_slippage, quote))
+}
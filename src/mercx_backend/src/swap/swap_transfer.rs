@@ -13,6 +13,10 @@ use crate::helpers::math_helpers::nat_is_zero;
 use crate::ic::address::Address;
 use crate::ic::address_helpers::get_address;
 use crate::ic::id::caller_id;
+use crate::compliance::compliance_policy::Operation;
+use crate::compliance::policy as compliance;
+use crate::compliance::rules;
+use crate::swap::calculate_amounts::calculate_amounts;
 use crate::stable_mercx_settings::mercx_settings_map;
 use crate::swap::return_pay_token::return_pay_token;
 use crate::swap::swap_calc::SwapCalc;
@@ -43,6 +47,7 @@ pub async fn swap_transfer(args: SwapArgs) -> Result<SwapReply, String> {
     )
     .await
     .map_err(|e| {
+        handlers::link(&transfer_ids, &[]);
         println!("❌ process_swap failed: {}", e);
         format!("process_swap error: {}", e)
     })?;
@@ -64,6 +69,8 @@ pub async fn swap_transfer(args: SwapArgs) -> Result<SwapReply, String> {
         ts,
     )
     .await;
+    let pool_ids: Vec<u32> = swaps.iter().map(|swap_calc| swap_calc.pool_id).collect();
+    handlers::link(&transfer_ids, &pool_ids);
 
   // Record snapshot for each pool involved in the swap
   for swap_calc in &swaps {
@@ -90,35 +97,18 @@ async fn process_swap(
         .inspect_err(|e| println!("Receive token lookup failed: {}", e))
         .map_err(|e| format!("Receive token error: {}", e))?;
 
-    // if receive_token.is_removed() {
-    //    // request_map::update_status(request_id, StatusCode::ReceiveTokenNotFound, None);
-    //     return_pay_token(
-
-    //         &caller_id,
-    //         pay_token,
-    //         pay_amount,
-    //         transfer_ids,
-    //         ts,
-    //     )
-    //     .await;
-    //     Err(format!("Req #{} failed. Receive token is suspended or removed", request_id))?
-    // }
+    if receive_token.is_removed() {
+       // request_map::update_status(request_id, StatusCode::ReceiveTokenNotFound, None);
+        return_pay_token(&caller_id, pay_token, pay_amount, transfer_ids, ts).await;
+        Err("Failed. Receive token is suspended or removed".to_string())?
+    }
      let receive_amount = args.receive_amount.as_ref();
 
-    // if pay_token.is_removed() {
-    //     //request_map::update_status(request_id, StatusCode::PayTokenNotFound, None);
-    //     return_pay_token(
-
-    //         &caller_id,
-    //         pay_token,
-    //         pay_amount,
-
-    //         transfer_ids,
-    //         ts,
-    //     )
-    //     .await;
-    //     Err(format!("Req #{} failed. Pay token is suspended or removed", request_id))?
-    // }
+    if pay_token.is_removed() {
+        //request_map::update_status(request_id, StatusCode::PayTokenNotFound, None);
+        return_pay_token(&caller_id, pay_token, pay_amount, transfer_ids, ts).await;
+        Err("Failed. Pay token is suspended or removed".to_string())?
+    }
     if nat_is_zero(pay_amount) {
         //   request_map::update_status(request_id, StatusCode::PayTokenAmountIsZero, None);
         return_pay_token(
@@ -151,6 +141,19 @@ async fn process_swap(
         },
         None => Address::PrincipalId(caller_id),
     };
+    if let Err(e) = compliance::check(Operation::Swap, caller_id.owner, &[pay_token, &receive_token], Some(&to_address)).await {
+        return_pay_token(&caller_id, pay_token, pay_amount, transfer_ids, ts).await;
+        Err(e)?
+    }
+    // token rules are checked against a quote, the pool is only updated once they pass
+    let rules_check = match calculate_amounts(pay_token, pay_amount, &receive_token, receive_amount, max_slippage) {
+        Ok((quote, ..)) => rules::check_swap(caller_id.owner, pay_token, &receive_token, &quote, &to_address).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = rules_check {
+        return_pay_token(&caller_id, pay_token, pay_amount, transfer_ids, ts).await;
+        Err(e)?
+    }
 
     let (receive_amount_with_fees_and_gas, mid_price, price, slippage, swaps) =
         match update_liquidity_pool(
@@ -208,6 +211,10 @@ async fn verify_transfer_token(
                 tx_id: TxId::BlockIndex(tx_id.clone()),
                 transfer_type: TransferType::Swap,       // This is a SWAP, not liquidity removal
                 ts,
+                account: Some(caller_id()),
+                pool_ids: Vec::new(),
+                request_id: None,
+                usd_price: None,
             });
             //request_map::update_status(request_id, StatusCode::VerifyPayTokenSuccess, None);
             Ok(transfer_id)
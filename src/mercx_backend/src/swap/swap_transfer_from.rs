@@ -3,6 +3,10 @@ use crate::swap::calculate_amounts::calculate_amounts;
 use crate::ic::general::get_time;
 use crate::helpers::math_helpers::nat_is_zero;
 use crate::ic::id::caller_id;
+use crate::compliance::compliance_policy::Operation;
+use crate::compliance::policy as compliance;
+use crate::compliance::rules;
+use crate::trading_hours::schedule::check_swaps_open;
 use crate::ic::address::Address;
 use crate::StableToken;
 use candid::Nat;
@@ -43,6 +47,7 @@ pub async fn swap_transfer_from(args: SwapArgs) -> Result<SwapReply, String> {
     )
     .await
     .inspect_err(|_| {
+        transfer_handlers::link(&transfer_ids, &[]);
       //  request_map::update_status(request_id, StatusCode::Failed, None);
       //  increase_consecutive_error(user_id);
      //   let _ = archive_to_kong_data(request_id);
@@ -62,6 +67,8 @@ pub async fn swap_transfer_from(args: SwapArgs) -> Result<SwapReply, String> {
         ts,
     )
     .await;
+    let pool_ids: Vec<u32> = swaps.iter().map(|swap_calc| swap_calc.pool_id).collect();
+    transfer_handlers::link(&transfer_ids, &pool_ids);
 
     // request_map::update_status(request_id, StatusCode::Success, None);
     // reset_consecutive_error(user_id);
@@ -84,6 +91,13 @@ async fn check_arguments(args: &SwapArgs) -> Result<( StableToken, Nat, StableTo
 
     let receive_token = handlers::get_by_token(&args.receive_token)?;
 
+    if pay_token.is_removed() {
+        Err("Pay token is suspended or removed".to_string())?;
+    }
+
+    if receive_token.is_removed() {
+        Err("Receive token is suspended or removed".to_string())?;
+    }
 
     // use specified max slippage or use default
     let max_slippage = args.max_slippage.unwrap_or(mercx_settings_map::get().default_max_slippage);
@@ -92,6 +106,7 @@ async fn check_arguments(args: &SwapArgs) -> Result<( StableToken, Nat, StableTo
         Some(ref address) => get_address(&receive_token, address)?,
         None => Address::PrincipalId(caller_id()),
     };
+    compliance::check(Operation::Swap, caller_id().owner, &[&pay_token, &receive_token], Some(&to_address)).await?;
     if nat_is_zero(&pay_amount) {
         Err("Pay amount is zero".to_string())?;
     }
@@ -118,8 +133,11 @@ async fn check_arguments(args: &SwapArgs) -> Result<( StableToken, Nat, StableTo
     // }
 
     // calculate receive_amount and swaps. do after user_id is created as it will be needed to calculate the receive_amount (user fee level)
-    // no needs to store the return values as it'll be called again in process_swap
-    calculate_amounts(&pay_token, &pay_amount, &receive_token, args.receive_amount.as_ref(), max_slippage)?;
+    // only the quote is kept, for the token rules. it'll be called again in process_swap
+    let (quote, _, _, _, swaps) = calculate_amounts(&pay_token, &pay_amount, &receive_token, args.receive_amount.as_ref(), max_slippage)?;
+    // checked again in update_liquidity_pool, this one saves pulling the pay token when the market is closed
+    check_swaps_open(&swaps)?;
+    rules::check_swap(caller_id().owner, &pay_token, &receive_token, &quote, &to_address).await?;
 
     Ok(( pay_token, pay_amount, receive_token, max_slippage, to_address))
 }
@@ -147,6 +165,10 @@ async fn transfer_from_token(
                 tx_id: TxId::BlockIndex(tx_id),
                 transfer_type: TransferType::Swap,       // This is a SWAP, not liquidity removal
                 ts,
+                account: Some(*from_principal_id),
+                pool_ids: Vec::new(),
+                request_id: None,
+                usd_price: None,
             });
             transfer_ids.push(transfer_id);
             //request_map::update_status(request_id, StatusCode::SendPayTokenSuccess, None);
@@ -5,6 +5,8 @@ use super::swap_calc::SwapCalc;
 
 use crate::helpers::math_helpers::{nat_add, nat_divide, nat_multiply, nat_subtract, nat_zero};
 use crate::pool::handlers;
+use crate::circuit_breaker::breaker::{observe_pool_price, pool_swaps_open};
+use crate::trading_hours::schedule::check_swaps_open;
 use crate::StableToken;
 
 pub fn update_liquidity_pool(
@@ -25,6 +27,13 @@ pub fn update_liquidity_pool(
         max_slippage,
     ) {
         Ok((receive_amount_with_fees_and_gas, price, mid_price, slippage, swaps)) => {
+            // every pool of the route must be within its trading hours
+            check_swaps_open(&swaps)?;
+            // and not halted by the price move trigger
+            for swap in &swaps {
+                pool_swaps_open(swap.pool_id)?;
+            }
+
             // request_map::update_status(request_id, StatusCode::CalculatePoolAmountsSuccess, None);
 
             // update the pool, in some cases there could be multiple pools
@@ -36,6 +45,7 @@ pub fn update_liquidity_pool(
                     Some(pool) => pool,
                     None => continue, // should not get here
                 };
+                let price_before = pool.get_price_as_f64();
 
                 //Means the direction is: token_0 → token_1
                 if swap.receive_token_id == pool.token_id_1 {
@@ -71,6 +81,7 @@ pub fn update_liquidity_pool(
                     pool.mercx_fee_0 = nat_add(&pool.mercx_fee_0, &mercx_fee_0);
                 }
                 handlers::update(&pool);
+                observe_pool_price(&pool, price_before);
             }
 
             //  request_map::update_status(request_id, StatusCode::UpdatePoolAmountsSuccess, None);
@@ -1,13 +1,12 @@
 use candid::{CandidType, Principal};
 use serde::{Deserialize, Serialize};
 
-use crate::token::stable_token::StableTokenId;
-use crate::stable_memory::TOKENS;
 use crate::StableToken;
 
 use crate::stable_mercx_settings::mercx_settings_map::inc_token_map_idx;
 
-use crate::token::handlers::exists_by_canister_id;
+use crate::token::handlers::{exists_by_canister_id, insert};
+use crate::rbac::guards::is_admin;
 
 /// Arguments for adding a token.
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
@@ -15,7 +14,7 @@ pub struct AddTokenArgs {
     pub canister_id: Principal,
 }
 
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_admin")]
 pub async fn add_token(canister_id: Principal) -> Result<StableToken, String> {
 
      // 🔒 Check if the token already exists by canister ID
@@ -28,9 +27,8 @@ pub async fn add_token(canister_id: Principal) -> Result<StableToken, String> {
     let token_id = inc_token_map_idx();
     token.token_id = token_id;
 
-    TOKENS.with(|tokens| {
-        tokens.borrow_mut().insert(StableTokenId(token_id), token.clone());
-    });
+    // rejects a symbol that is already listed
+    insert(&token)?;
 
     Ok(token)
 }
@@ -0,0 +1,98 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+use crate::lp_metadata::stable_lp_metadata::StableLpMetadataId;
+use crate::stable_memory::{LPMETADATA, POOLS, TOKENS, TRANSFERS, TRANSFER_ARCHIVE};
+use crate::token::stable_token::StableTokenId;
+
+/// A record that points at a token or LP token that no longer exists
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct DanglingReference {
+    pub source: String, // POOLS, TRANSFERS, TRANSFER_ARCHIVE or LPMETADATA
+    pub id: u64,        // pool_id, transfer_id or LP token_id of the record
+    pub token_id: u32,  // missing token_id (LP token_id for pool.lp_token_id)
+}
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct TokenConsistencyReport {
+    pub pools_checked: u32,
+    pub transfers_checked: u64,
+    pub lp_tokens_checked: u32,
+    pub dangling: Vec<DanglingReference>,
+}
+
+fn token_exists(token_id: u32) -> bool {
+    TOKENS.with(|tokens| tokens.borrow().contains_key(&StableTokenId(token_id)))
+}
+
+/// Report token_id references in POOLS, TRANSFERS, TRANSFER_ARCHIVE and LPMETADATA that no longer resolve.
+/// - pools whose token_id_0, token_id_1 or lp_token_id is missing
+/// - transfers whose token_id is missing
+/// - LP tokens that are not used by any pool
+#[ic_cdk::query]
+pub fn check_token_consistency() -> TokenConsistencyReport {
+    let mut dangling = Vec::new();
+
+    let mut pools_checked = 0;
+    let mut pool_lp_token_ids = Vec::new();
+    POOLS.with(|pools| {
+        for (_, pool) in pools.borrow().iter() {
+            pools_checked += 1;
+            for token_id in [pool.token_id_0, pool.token_id_1] {
+                if !token_exists(token_id) {
+                    dangling.push(DanglingReference {
+                        source: "POOLS".to_string(),
+                        id: pool.pool_id as u64,
+                        token_id,
+                    });
+                }
+            }
+            let lp_token_exists = LPMETADATA.with(|m| m.borrow().contains_key(&StableLpMetadataId(pool.lp_token_id)));
+            if !lp_token_exists {
+                dangling.push(DanglingReference {
+                    source: "POOLS".to_string(),
+                    id: pool.pool_id as u64,
+                    token_id: pool.lp_token_id,
+                });
+            }
+            pool_lp_token_ids.push(pool.lp_token_id);
+        }
+    });
+
+    let mut transfers_checked = 0;
+    for (source, transfers) in [("TRANSFERS", &TRANSFERS), ("TRANSFER_ARCHIVE", &TRANSFER_ARCHIVE)] {
+        transfers.with(|transfers| {
+            for (_, transfer) in transfers.borrow().iter() {
+                transfers_checked += 1;
+                if !token_exists(transfer.token_id) {
+                    dangling.push(DanglingReference {
+                        source: source.to_string(),
+                        id: transfer.transfer_id,
+                        token_id: transfer.token_id,
+                    });
+                }
+            }
+        });
+    }
+
+    let mut lp_tokens_checked = 0;
+    LPMETADATA.with(|lp_tokens| {
+        for (_, lp_token) in lp_tokens.borrow().iter() {
+            lp_tokens_checked += 1;
+            if !pool_lp_token_ids.contains(&lp_token.token_id) {
+                dangling.push(DanglingReference {
+                    source: "LPMETADATA".to_string(),
+                    id: lp_token.token_id as u64,
+                    token_id: lp_token.token_id,
+                });
+            }
+        }
+    });
+
+    TokenConsistencyReport {
+        pools_checked,
+        transfers_checked,
+        lp_tokens_checked,
+        dangling,
+    }
+}
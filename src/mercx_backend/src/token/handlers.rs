@@ -1,8 +1,13 @@
-use crate::token::stable_token::{StableToken, StableTokenId};
-use crate::stable_memory::TOKENS;
+use crate::token::stable_token::{StableToken, StableTokenId, TokenStatus};
+use crate::stable_memory::{TOKENS, TOKEN_CANISTER_INDEX, TOKEN_SYMBOL_INDEX};
 use candid::Principal;
 use crate::stable_mercx_settings::mercx_settings_map::reset_token_map_idx;
-use crate::stable_memory::POOLS;
+use crate::stable_memory::{POOLS, TRANSFERS, TRANSFER_ARCHIVE};
+use crate::token::token_metadata::{get_token_metadata, validate_logo};
+use crate::block_log::dex_block::log_admin_action;
+use crate::rbac::guards::{is_admin, is_compliance};
+use crate::compliance::compliance_policy::ComplianceLevel;
+use crate::compliance::token_rules::TokenRules;
 pub fn get_by_token_id(token_id: u32) -> Option<StableToken> {
     TOKENS.with(|m| m.borrow().get(&StableTokenId(token_id)))
 }
@@ -18,45 +23,41 @@ pub fn get_all_tokens() -> Vec<StableToken> {
     })
 }
 
+/// prefix for unambiguous token strings, e.g. "IC.ryjl3-tyaaa-aaaaa-aaaba-cai"
+pub const CANISTER_ID_PREFIX: &str = "IC.";
+
 pub fn exists_by_canister_id(canister_id: &Principal) -> bool {
-    TOKENS.with(|m| {
-        m.borrow()
-            .iter()
-            .any(|(_, token)| token.canister_id == *canister_id)
-    })
+    TOKEN_CANISTER_INDEX.with(|m| m.borrow().contains_key(canister_id))
 }
 
 pub fn get_by_symbol(symbol: &str) -> Result<StableToken, String> {
-    let query = symbol.to_lowercase();
-
-    TOKENS.with(|tokens| {
-        tokens
-            .borrow()
-            .iter()
-            .find(|(_, token)| token.symbol.to_lowercase() == query)
-            .map(|(_, token)| token.clone())
-    })
-    .ok_or_else(|| format!("Token with symbol '{}' not found", symbol))
+    TOKEN_SYMBOL_INDEX
+        .with(|m| m.borrow().get(&symbol.to_lowercase()))
+        .and_then(get_by_token_id)
+        .ok_or_else(|| format!("Token with symbol '{}' not found", symbol))
 }
 
 
 pub fn get_by_canister_id(canister_id: &Principal) -> Option<StableToken> {
-    TOKENS.with(|tokens| {
-        tokens
-            .borrow()
-            .iter()
-            .find_map(|(_, token)| {
-                if &token.canister_id == canister_id {
-                    Some(token.clone())
-                } else {
-                    None
-                }
-            })
-    })
+    TOKEN_CANISTER_INDEX
+        .with(|m| m.borrow().get(canister_id))
+        .and_then(get_by_token_id)
 }
 
+/// Parse "IC.<canister_id>" or a bare canister id
+pub fn parse_canister_id(token: &str) -> Result<Principal, String> {
+    let canister_id = token.strip_prefix(CANISTER_ID_PREFIX).unwrap_or(token);
+    Principal::from_text(canister_id).map_err(|e| format!("Invalid canister id '{}': {}", canister_id, e))
+}
 
+/// Resolve a token string from the API.
+/// "IC.<canister_id>" only matches by canister id, anything else is tried as a symbol then as a bare canister id.
 pub fn get_by_token(token: &str) -> Result<StableToken, String> {
+    if token.starts_with(CANISTER_ID_PREFIX) {
+        let canister_id = parse_canister_id(token)?;
+        return get_by_canister_id(&canister_id).ok_or_else(|| format!("Token '{}' not found", token));
+    }
+
     // Try by symbol
     if let Ok(token) = get_by_symbol(token) {
         return Ok(token);
@@ -75,14 +76,71 @@ pub fn get_by_token(token: &str) -> Result<StableToken, String> {
     ))
 }
 
+/// Insert a new token and its index entries. Symbols are unique, case insensitive.
+pub fn insert(token: &StableToken) -> Result<(), String> {
+    if exists_by_canister_id(&token.canister_id) {
+        Err(format!("Token with canister_id {} already exists", token.canister_id))?
+    }
+    if let Ok(existing) = get_by_symbol(&token.symbol) {
+        Err(format!(
+            "Token symbol {} is already used by {}. Use {}{} to refer to it",
+            token.symbol, existing.canister_id, CANISTER_ID_PREFIX, existing.canister_id
+        ))?
+    }
+
+    TOKENS.with(|m| m.borrow_mut().insert(StableTokenId(token.token_id), token.clone()));
+    TOKEN_CANISTER_INDEX.with(|m| m.borrow_mut().insert(token.canister_id, token.token_id));
+    TOKEN_SYMBOL_INDEX.with(|m| m.borrow_mut().insert(token.symbol.to_lowercase(), token.token_id));
+    Ok(())
+}
+
+fn remove(token_id: &StableTokenId) {
+    if let Some(token) = TOKENS.with(|m| m.borrow_mut().remove(token_id)) {
+        TOKEN_CANISTER_INDEX.with(|m| m.borrow_mut().remove(&token.canister_id));
+        TOKEN_SYMBOL_INDEX.with(|m| {
+            let mut index = m.borrow_mut();
+            let symbol = token.symbol.to_lowercase();
+            // only drop the symbol if it points at this token
+            if index.get(&symbol) == Some(token_id.0) {
+                index.remove(&symbol);
+            }
+        });
+    }
+}
+
+/// Rebuild the canister id and symbol indexes from TOKENS.
+/// Tokens stored before the indexes existed are backfilled here. On a symbol collision the
+/// lowest token_id keeps the symbol, the others are only reachable by IC.<canister_id>.
+pub fn rebuild_token_indexes() {
+    TOKEN_CANISTER_INDEX.with(|m| m.borrow_mut().clear_new());
+    TOKEN_SYMBOL_INDEX.with(|m| m.borrow_mut().clear_new());
+
+    for token in get_all_tokens() {
+        TOKEN_CANISTER_INDEX.with(|m| m.borrow_mut().insert(token.canister_id, token.token_id));
+        let symbol = token.symbol.to_lowercase();
+        match TOKEN_SYMBOL_INDEX.with(|m| m.borrow().get(&symbol)) {
+            Some(token_id) => ic_cdk::println!(
+                "⚠️ Symbol {} used by token {} and {}, token {} only resolves by {}{}",
+                token.symbol, token_id, token.token_id, token.token_id, CANISTER_ID_PREFIX, token.canister_id
+            ),
+            None => {
+                TOKEN_SYMBOL_INDEX.with(|m| m.borrow_mut().insert(symbol, token.token_id));
+            }
+        }
+    }
+}
+
 #[cfg(not(feature = "prod"))]
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_admin")]
 fn reset_tokens() -> Result<String, String> {
     TOKENS.with(|tokens| {
         tokens.borrow_mut().clear_new(); // `clear_new()` btmsh kolo remove law hanmsh haga specific
     });
+    TOKEN_CANISTER_INDEX.with(|m| m.borrow_mut().clear_new());
+    TOKEN_SYMBOL_INDEX.with(|m| m.borrow_mut().clear_new());
 
     reset_token_map_idx();
+    log_admin_action("reset_tokens", "");
 
     Ok("✅ Tokens memory cleared".to_string())
 }
@@ -99,19 +157,10 @@ fn reset_tokens() -> Result<String, String> {
 //         None => Err(format!("❌ Token with ID {} not found.", id)),
 //     }
 // }
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_admin")]
 fn delete_token_by_canister_id(canister_id: Principal) -> Result<String, String> {
-    // Step 1: Find token ID from TOKENS map
-    let maybe_token_id = TOKENS.with(|tokens| {
-        let tokens = tokens.borrow();
-        tokens.iter().find_map(|(id, token)| {
-            if token.canister_id == canister_id {
-                Some(id.clone())
-            } else {
-                None
-            }
-        })
-    });
+    // Step 1: Find token ID from the canister index
+    let maybe_token_id = TOKEN_CANISTER_INDEX.with(|m| m.borrow().get(&canister_id)).map(StableTokenId);
 
     let token_id = match maybe_token_id {
         Some(id) => id,
@@ -126,13 +175,117 @@ fn delete_token_by_canister_id(canister_id: Principal) -> Result<String, String>
     });
 
     if is_used_in_pool {
-        return Err("Cannot delete token it is currently used in a pool.".to_string());
+        return Err("Cannot delete token it is currently used in a pool. Delist it instead.".to_string());
+    }
+
+    // transfers keep pointing at the token for history
+    let is_used_in_transfers = [&TRANSFERS, &TRANSFER_ARCHIVE].iter().any(|transfers| {
+        transfers.with(|transfers| transfers.borrow().iter().any(|(_, transfer)| transfer.token_id == token_id.0))
+    });
+
+    if is_used_in_transfers {
+        return Err("Cannot delete token it has transfer history. Delist it instead.".to_string());
     }
 
     // Step 3: Delete token if not in use
+    remove(&token_id);
+    log_admin_action("delete_token", &canister_id.to_text());
+
+    Ok(format!("✅ Token with canister ID {} deleted.", canister_id))
+}
+
+/// Move a token to a new listing state.
+/// Delisting blocks new pools and swaps but keeps the token record so existing pools,
+/// transfers and liquidity removals keep resolving it.
+#[ic_cdk::update(guard = "is_admin")]
+fn update_token_status(canister_id: Principal, status: TokenStatus) -> Result<StableToken, String> {
+    let token = get_by_canister_id(&canister_id).ok_or("❌ Token not found.")?;
+
+    let updated_token = StableToken {
+        status: Some(status),
+        ..token
+    };
     TOKENS.with(|tokens| {
-        tokens.borrow_mut().remove(&token_id);
+        tokens
+            .borrow_mut()
+            .insert(StableTokenId(updated_token.token_id), updated_token.clone());
     });
 
-    Ok(format!("✅ Token with canister ID {} deleted.", canister_id))
+    ic_cdk::println!("Token {} status set to {:?}", updated_token.symbol, updated_token.status());
+    log_admin_action("update_token_status", &format!("{} {:?}", canister_id, updated_token.status()));
+
+    Ok(updated_token)
+}
+
+/// Level needed to trade or provide liquidity for a token, on top of the compliance policy.
+/// None drops the override so the token follows the policy again.
+#[ic_cdk::update(guard = "is_compliance")]
+fn set_token_compliance_level(canister_id: Principal, level: Option<ComplianceLevel>) -> Result<StableToken, String> {
+    let token = get_by_canister_id(&canister_id).ok_or("❌ Token not found.")?;
+    let updated_token = StableToken {
+        compliance_level: level,
+        ..token
+    };
+    TOKENS.with(|tokens| {
+        tokens
+            .borrow_mut()
+            .insert(StableTokenId(updated_token.token_id), updated_token.clone());
+    });
+
+    log_admin_action("set_token_compliance_level", &format!("{} {:?}", canister_id, level));
+
+    Ok(updated_token)
+}
+
+/// Transfer restrictions of a tokenized security, None removes them
+#[ic_cdk::update(guard = "is_compliance")]
+fn set_token_rules(canister_id: Principal, rules: Option<TokenRules>) -> Result<StableToken, String> {
+    let rules = rules.map(TokenRules::validate).transpose()?;
+    let token = get_by_canister_id(&canister_id).ok_or("❌ Token not found.")?;
+    let updated_token = StableToken {
+        rules: rules.clone(),
+        ..token
+    };
+    TOKENS.with(|tokens| {
+        tokens
+            .borrow_mut()
+            .insert(StableTokenId(updated_token.token_id), updated_token.clone());
+    });
+
+    log_admin_action("set_token_rules", &format!("{} {:?}", canister_id, rules));
+
+    Ok(updated_token)
+}
+
+/// Admin override for a token's logo, for ledgers with a missing or bad icrc1:logo.
+/// Some(logo) pins the logo so the metadata refresh keeps it, None drops the override and
+/// goes back to the ledger's logo.
+
+#[ic_cdk::update(guard = "is_admin")]
+async fn set_token_logo(canister_id: Principal, logo: Option<String>) -> Result<StableToken, String> {
+    let (logo, logo_overridden) = match logo {
+        Some(logo) => {
+            validate_logo(&logo)?;
+            (Some(logo), Some(true))
+        }
+        None => (get_token_metadata(canister_id).await?.logo, None),
+    };
+
+    // read after the await so concurrent updates are kept
+    let token = get_by_canister_id(&canister_id).ok_or("❌ Token not found.")?;
+    let updated_token = StableToken {
+        logo,
+        logo_overridden,
+        ..token
+    };
+    TOKENS.with(|tokens| {
+        tokens
+            .borrow_mut()
+            .insert(StableTokenId(updated_token.token_id), updated_token.clone());
+    });
+
+    ic_cdk::println!("Token {} logo updated, override: {}", updated_token.symbol, updated_token.is_logo_overridden());
+    log_admin_action("set_token_logo", &format!("{} override: {}", canister_id, updated_token.is_logo_overridden()));
+
+    Ok(updated_token)
 }
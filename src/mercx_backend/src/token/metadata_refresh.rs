@@ -0,0 +1,172 @@
+use candid::{CandidType, Nat, Principal};
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::time::Duration;
+
+use crate::ic::general::{get_decimals, get_fee, get_name};
+use crate::stable_memory::TOKENS;
+use crate::token::handlers::{get_all_tokens, get_by_token_id};
+use crate::token::stable_token::{supported_standards, StableToken, StableTokenId};
+use crate::token::token_metadata::{get_token_metadata, TokenMetadata};
+use crate::rbac::guards::is_operator;
+
+// ledgers rarely change fee or name, a few refreshes a day is enough
+const TOKEN_REFRESH_INTERVAL_SECS: u64 = 6 * 60 * 60;
+
+thread_local! {
+    static REFRESH_IN_PROGRESS: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Result of re-querying one token's ledger
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct TokenRefreshReport {
+    pub token_id: u32,
+    pub symbol: String,
+    pub changes: Vec<String>,      // "field: old -> new" for every updated field
+    pub decimals_changed: bool,    // ledger reports different decimals, stored value is NOT updated
+    pub error: Option<String>,     // ledger call failed, token left untouched
+}
+
+/// Start the periodic refresh. Timers do not survive upgrades so this runs from init and post_upgrade.
+pub fn start_token_refresh_timer() {
+    ic_cdk_timers::set_timer_interval(Duration::from_secs(TOKEN_REFRESH_INTERVAL_SECS), || {
+        ic_cdk::spawn(async {
+            let reports = refresh_all_tokens().await;
+            let changed = reports.iter().filter(|r| !r.changes.is_empty()).count();
+            ic_cdk::println!("Token metadata refresh done: {} tokens, {} changed", reports.len(), changed);
+        })
+    });
+}
+
+/// Re-query fee, name, decimals, supported standards and icrc1_metadata for every token and update TOKENS.
+/// Returns an empty list if a refresh is already running.
+pub async fn refresh_all_tokens() -> Vec<TokenRefreshReport> {
+    let Some(_guard) = RefreshGuard::acquire() else {
+        ic_cdk::println!("Token metadata refresh already in progress, skipping");
+        return Vec::new();
+    };
+
+    let mut reports = Vec::new();
+    for token in get_all_tokens() {
+        reports.push(refresh_token(&token).await);
+    }
+
+    reports
+}
+
+/// Holds REFRESH_IN_PROGRESS while alive. Dropping it clears the flag, which also happens
+/// when a callback traps and the cleanup drops the refresh future
+struct RefreshGuard;
+
+impl RefreshGuard {
+    fn acquire() -> Option<Self> {
+        (!REFRESH_IN_PROGRESS.with(|f| f.replace(true))).then_some(RefreshGuard)
+    }
+}
+
+impl Drop for RefreshGuard {
+    fn drop(&mut self) {
+        REFRESH_IN_PROGRESS.with(|f| f.set(false));
+    }
+}
+
+async fn refresh_token(token: &StableToken) -> TokenRefreshReport {
+    let mut report = TokenRefreshReport {
+        token_id: token.token_id,
+        symbol: token.symbol.clone(),
+        changes: Vec::new(),
+        decimals_changed: false,
+        error: None,
+    };
+
+    let fetched = fetch_ledger_metadata(token.canister_id).await;
+    let (name, fee, decimals, (icrc1, icrc2, icrc3), metadata) = match fetched {
+        Ok(fetched) => fetched,
+        Err(e) => {
+            ic_cdk::println!("❌ Token {} metadata refresh failed: {}", token.symbol, e);
+            report.error = Some(e);
+            return report;
+        }
+    };
+
+    // re-read after the awaits so a concurrent status change is not overwritten
+    let Some(mut current) = get_by_token_id(token.token_id) else {
+        report.error = Some("Token removed during refresh".to_string());
+        return report;
+    };
+
+    if current.name != name {
+        report.changes.push(format!("name: {} -> {}", current.name, name));
+        current.name = name;
+    }
+    if current.fee != fee {
+        report.changes.push(format!("fee: {} -> {}", current.fee, fee));
+        current.fee = fee;
+    }
+    if (current.icrc1, current.icrc2, current.icrc3) != (icrc1, icrc2, icrc3) {
+        report.changes.push(format!(
+            "standards: icrc1={} icrc2={} icrc3={} -> icrc1={} icrc2={} icrc3={}",
+            current.icrc1, current.icrc2, current.icrc3, icrc1, icrc2, icrc3
+        ));
+        current.icrc1 = icrc1;
+        current.icrc2 = icrc2;
+        current.icrc3 = icrc3;
+    }
+    if !current.is_logo_overridden() && current.logo != metadata.logo {
+        report.changes.push("logo".to_string()); // may be a large data URL, not logged
+        current.logo = metadata.logo;
+    }
+    if current.description != metadata.description {
+        report.changes.push(format!("description: {:?} -> {:?}", current.description, metadata.description));
+        current.description = metadata.description;
+    }
+    if current.metadata.as_ref() != Some(&metadata.extra) {
+        report.changes.push("metadata".to_string());
+        current.metadata = Some(metadata.extra);
+    }
+    // pool balances are stored in the old units, changing decimals needs a manual migration
+    if current.decimals != decimals {
+        report.decimals_changed = true;
+        ic_cdk::println!(
+            "🚨 ALERT: token {} ({}) decimals changed on ledger {} -> {}. Stored value kept, manual action needed",
+            current.symbol,
+            current.canister_id,
+            current.decimals,
+            decimals
+        );
+    }
+
+    if !report.changes.is_empty() {
+        ic_cdk::println!("Token {} metadata updated: {}", current.symbol, report.changes.join(", "));
+        TOKENS.with(|tokens| {
+            tokens.borrow_mut().insert(StableTokenId(current.token_id), current);
+        });
+    }
+
+    report
+}
+
+async fn fetch_ledger_metadata(
+    canister_id: Principal,
+) -> Result<(String, Nat, u8, (bool, bool, bool), TokenMetadata), String> {
+    let name = get_name(canister_id)
+        .await
+        .map_err(|e| format!("get_name failed: {}", e))?;
+    let fee = get_fee(canister_id)
+        .await
+        .map_err(|e| format!("get_fee failed: {}", e))?;
+    let decimals = get_decimals(canister_id)
+        .await
+        .map_err(|e| format!("get_decimals failed: {}", e))?;
+    let standards = supported_standards(canister_id)
+        .await
+        .map_err(|e| format!("get_supported_standards failed: {}", e))?;
+    let metadata = get_token_metadata(canister_id).await?;
+    Ok((name, fee, decimals, standards, metadata))
+}
+
+/// Admin trigger for the periodic token metadata refresh
+#[ic_cdk::update(guard = "is_operator")]
+pub async fn refresh_token_metadata() -> Result<Vec<TokenRefreshReport>, String> {
+    Ok(refresh_all_tokens().await)
+}
@@ -1,3 +1,6 @@
 pub mod stable_token;
 pub mod handlers;
-pub mod add_token;
\ No newline at end of file
+pub mod add_token;
+pub mod consistency;
+pub mod metadata_refresh;
+pub mod token_metadata;
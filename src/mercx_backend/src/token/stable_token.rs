@@ -1,9 +1,14 @@
 use crate::ic::general::get_supported_standards;
 use crate::ic::general::{get_decimals, get_fee, get_name, get_symbol};
+use crate::token::token_metadata::get_token_metadata;
 use candid::{CandidType, Decode, Encode, Nat, Principal};
+use icrc_ledger_types::icrc::generic_metadata_value::MetadataValue;
 use ic_stable_structures::{storable::Bound, Storable};
 use serde::{Deserialize, Serialize};
 use std::borrow::Cow;
+use crate::stable_schema::envelope::impl_versioned_candid;
+use crate::compliance::compliance_policy::ComplianceLevel;
+use crate::compliance::token_rules::TokenRules;
 #[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 pub struct StableTokenId(pub u32);
 
@@ -24,6 +29,16 @@ impl Storable for StableTokenId {
     };
 }
 
+/// Listing state of a token.
+/// Tokens are never removed from TOKENS once pools or transfers point at them, they are moved
+/// through these states instead so history and liquidity removals keep working.
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TokenStatus {
+    Active,     // fully tradable
+    Deprecated, // no new pools or liquidity, swaps and removals still allowed
+    Delisted,   // no new pools, liquidity or swaps. only liquidity removals
+}
+
 #[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
 pub struct StableToken {
     pub token_id: u32,
@@ -35,6 +50,13 @@ pub struct StableToken {
     pub icrc1: bool,
     pub icrc2: bool,
     pub icrc3: bool,
+    pub status: Option<TokenStatus>, // None for tokens stored before listing status existed, treated as Active
+    pub logo: Option<String>,        // icrc1:logo, https or data:image URL
+    pub description: Option<String>, // icrc1:description
+    pub metadata: Option<Vec<(String, MetadataValue)>>, // remaining icrc1_metadata entries
+    pub logo_overridden: Option<bool>, // logo set by an admin, ledger refresh leaves it alone
+    pub compliance_level: Option<ComplianceLevel>, // stricter level for tokenized securities, None follows the policy
+    pub rules: Option<TokenRules>,                 // transfer restrictions of tokenized securities, None for none
 }
 
 impl StableToken {
@@ -51,21 +73,11 @@ impl StableToken {
         let fee = get_fee(canister_id)
             .await
             .map_err(|e| format!("get_fee failed: {}", e))?;
-        let (icrc1, icrc2, icrc3) = match get_supported_standards(canister_id).await {
-            Ok(supported_standards) => {
-                let icrc1 = supported_standards
-                    .iter()
-                    .any(|standard| standard.name == "ICRC-1");
-                let icrc2 = supported_standards
-                    .iter()
-                    .any(|standard| standard.name == "ICRC-2");
-                let icrc3 = supported_standards
-                    .iter()
-                    .any(|standard| standard.name == "ICRC-3");
-                (icrc1, icrc2, icrc3)
-            }
-            Err(_) => (true, false, false), // should at least support ICRC-1 if it made it this far
-        };
+        let (icrc1, icrc2, icrc3) = supported_standards(canister_id)
+            .await
+            .unwrap_or((true, false, false)); // should at least support ICRC-1 if it made it this far
+        // logo and metadata are optional, a ledger without icrc1_metadata can still be listed
+        let metadata = get_token_metadata(canister_id).await.unwrap_or_default();
         Ok(Self {
             token_id: 0,
             name,
@@ -76,6 +88,13 @@ impl StableToken {
             icrc1,
             icrc2,
             icrc3,
+            status: Some(TokenStatus::Active),
+            logo: metadata.logo,
+            description: metadata.description,
+            metadata: Some(metadata.extra),
+            logo_overridden: None,
+            compliance_level: None,
+            rules: None,
         })
     }
 
@@ -112,6 +131,39 @@ impl StableToken {
         self.fee.clone()
  }
 
+    pub fn status(&self) -> TokenStatus {
+        self.status.clone().unwrap_or(TokenStatus::Active)
+    }
+
+    /// token can be used for new pools and liquidity
+    pub fn is_active(&self) -> bool {
+        self.status() == TokenStatus::Active
+    }
+
+    /// token is delisted, swaps are blocked
+    pub fn is_removed(&self) -> bool {
+        self.status() == TokenStatus::Delisted
+    }
+
+    pub fn is_logo_overridden(&self) -> bool {
+        self.logo_overridden.unwrap_or(false)
+    }
+
+}
+
+/// (icrc1, icrc2, icrc3) flags reported by the ledger's icrc1_supported_standards
+pub async fn supported_standards(canister_id: Principal) -> Result<(bool, bool, bool), String> {
+    let supported_standards = get_supported_standards(canister_id).await?;
+    let icrc1 = supported_standards
+        .iter()
+        .any(|standard| standard.name == "ICRC-1");
+    let icrc2 = supported_standards
+        .iter()
+        .any(|standard| standard.name == "ICRC-2");
+    let icrc3 = supported_standards
+        .iter()
+        .any(|standard| standard.name == "ICRC-3");
+    Ok((icrc1, icrc2, icrc3))
 }
 
 pub fn symbol(token_0: &StableToken, token_1: &StableToken) -> String {
@@ -119,18 +171,10 @@ pub fn symbol(token_0: &StableToken, token_1: &StableToken) -> String {
 }
 
 
-impl Storable for StableToken {
-    fn to_bytes(&self) -> Cow<'_, [u8]> {
-        Cow::Owned(Encode!(self).unwrap())
-    }
-
-    fn from_bytes(bytes: Cow<'_, [u8]>) -> Self {
-        Decode!(bytes.as_ref(), StableToken)
-            .unwrap_or_else(|e| ic_cdk::trap(&format!("❌ Failed to decode StableToken: {}", e)))
-    }
-
-    const BOUND: Bound = Bound::Bounded {
-        max_size: 2048, // adjust depending on your expected size
+impl_versioned_candid!(
+    StableToken,
+    Bound::Bounded {
+        max_size: 48 * 1024, // logo (MAX_LOGO_SIZE) + description + extra metadata entries
         is_fixed_size: false,
-    };
-}
+    }
+);
@@ -0,0 +1,81 @@
+use candid::Principal;
+use icrc_ledger_types::icrc::generic_metadata_value::MetadataValue;
+
+use crate::ic::general::get_metadata;
+
+// size limits keep a StableToken under its storage bound (see StableToken BOUND)
+pub const MAX_LOGO_SIZE: usize = 32 * 1024; // bytes, data:image URLs included
+pub const MAX_DESCRIPTION_SIZE: usize = 1024;
+pub const MAX_EXTRA_METADATA_ENTRIES: usize = 16;
+pub const MAX_METADATA_KEY_SIZE: usize = 64;
+pub const MAX_METADATA_VALUE_SIZE: usize = 256;
+
+// entries already stored in their own StableToken fields
+const STORED_KEYS: [&str; 7] = [
+    "icrc1:name",
+    "icrc1:symbol",
+    "icrc1:decimals",
+    "icrc1:fee",
+    "icrc1:logo",
+    "logo_url",
+    "icrc1:description",
+];
+
+/// Validated subset of a ledger's icrc1_metadata
+#[derive(Debug, Clone, Default)]
+pub struct TokenMetadata {
+    pub logo: Option<String>,
+    pub description: Option<String>,
+    pub extra: Vec<(String, MetadataValue)>,
+}
+
+/// logo must be an https or data:image URL no larger than MAX_LOGO_SIZE
+pub fn validate_logo(logo: &str) -> Result<(), String> {
+    if logo.len() > MAX_LOGO_SIZE {
+        Err(format!("Logo is {} bytes, max is {}", logo.len(), MAX_LOGO_SIZE))?
+    }
+    if !logo.starts_with("https://") && !logo.starts_with("data:image/") {
+        Err("Logo must be an https:// or data:image/ URL")?
+    }
+    Ok(())
+}
+
+/// Fetch icrc1_metadata and keep only entries that pass the size limits.
+/// Invalid entries are dropped and logged, they never fail the call.
+pub async fn get_token_metadata(canister_id: Principal) -> Result<TokenMetadata, String> {
+    let entries = get_metadata(canister_id)
+        .await
+        .map_err(|e| format!("get_metadata failed: {}", e))?;
+
+    let mut metadata = TokenMetadata::default();
+    for (key, value) in entries {
+        match (key.as_str(), value) {
+            ("icrc1:logo" | "logo_url", MetadataValue::Text(logo)) => match validate_logo(&logo) {
+                Ok(()) => metadata.logo = Some(logo),
+                Err(e) => ic_cdk::println!("Token {} logo dropped: {}", canister_id, e),
+            },
+            ("icrc1:description", MetadataValue::Text(description)) => {
+                if description.len() <= MAX_DESCRIPTION_SIZE {
+                    metadata.description = Some(description);
+                } else {
+                    ic_cdk::println!("Token {} description dropped: {} bytes", canister_id, description.len());
+                }
+            }
+            (key, _) if STORED_KEYS.contains(&key) => {}
+            (_, MetadataValue::Blob(_)) => {} // blobs are not served
+            (_, value) => {
+                let too_big = match &value {
+                    MetadataValue::Text(text) => text.len() > MAX_METADATA_VALUE_SIZE,
+                    _ => false,
+                };
+                if key.len() > MAX_METADATA_KEY_SIZE || too_big {
+                    ic_cdk::println!("Token {} metadata entry {} dropped: too large", canister_id, key);
+                } else if metadata.extra.len() < MAX_EXTRA_METADATA_ENTRIES {
+                    metadata.extra.push((key, value));
+                }
+            }
+        }
+    }
+
+    Ok(metadata)
+}
@@ -0,0 +1,3 @@
+pub mod stable_trading_schedule;
+pub mod schedule;
+pub mod trading_hours_reply;
@@ -0,0 +1,77 @@
+//! Market hours of pools of tokenized equities.
+//! Swaps through a pool with a schedule are refused outside its sessions, liquidity removals are not.
+use super::stable_trading_schedule::StableTradingSchedule;
+use super::trading_hours_reply::TradingHoursReply;
+use crate::block_log::dex_block::log_admin_action;
+use crate::ic::general::get_time;
+use crate::pool::handlers;
+use crate::pool::stable_pool::{StablePool, StablePoolId};
+use crate::rbac::guards::is_admin;
+use crate::stable_memory::{POOLS, POOL_SCHEDULES};
+use crate::swap::swap_calc::SwapCalc;
+
+pub fn get(pool_id: u32) -> Option<StableTradingSchedule> {
+    POOL_SCHEDULES.with(|m| m.borrow().get(&StablePoolId(pool_id)))
+}
+
+fn symbol(pool: &StablePool) -> String {
+    format!("{}_{}", pool.symbol_0(), pool.symbol_1())
+}
+
+/// Refuses a swap when any pool of its route is outside its trading hours
+pub fn check_swaps_open(swaps: &[SwapCalc]) -> Result<(), String> {
+    let now = get_time();
+    for swap in swaps {
+        let Some(schedule) = get(swap.pool_id) else {
+            continue;
+        };
+        if !schedule.is_open(now) {
+            let pool = handlers::get_by_pool_id(swap.pool_id).ok_or("❌ Pool not found.")?;
+            match schedule.next_open(now) {
+                Some(next_open) => Err(format!("❌ {} is closed for trading until {}", symbol(&pool), next_open))?,
+                None => Err(format!("❌ {} has no upcoming trading session", symbol(&pool)))?,
+            }
+        }
+    }
+    Ok(())
+}
+
+fn to_trading_hours_reply(pool: &StablePool, now: u64) -> TradingHoursReply {
+    let schedule = get(pool.pool_id);
+    TradingHoursReply {
+        pool_id: pool.pool_id,
+        symbol: symbol(pool),
+        is_open: schedule.as_ref().is_none_or(|schedule| schedule.is_open(now)),
+        next_open: schedule.as_ref().and_then(|schedule| schedule.next_open(now)),
+        next_close: schedule.as_ref().and_then(|schedule| schedule.next_close(now)),
+        schedule,
+    }
+}
+
+/// Trading schedule of a pool, None lets it trade around the clock again
+#[ic_cdk::update(guard = "is_admin")]
+fn set_pool_trading_hours(pool_id: u32, schedule: Option<StableTradingSchedule>) -> Result<TradingHoursReply, String> {
+    let pool = handlers::get_by_pool_id(pool_id).ok_or("❌ Pool not found.")?;
+    let schedule = schedule.map(StableTradingSchedule::validate).transpose()?;
+    POOL_SCHEDULES.with(|m| match &schedule {
+        Some(schedule) => m.borrow_mut().insert(StablePoolId(pool_id), schedule.clone()),
+        None => m.borrow_mut().remove(&StablePoolId(pool_id)),
+    });
+
+    log_admin_action("set_pool_trading_hours", &format!("{} {:?}", symbol(&pool), schedule));
+
+    Ok(to_trading_hours_reply(&pool, get_time()))
+}
+
+/// Open state and next open and close times of every pool
+#[ic_cdk::query]
+fn get_trading_hours() -> Vec<TradingHoursReply> {
+    let now = get_time();
+    POOLS.with(|pools| {
+        pools
+            .borrow()
+            .iter()
+            .map(|(_, pool)| to_trading_hours_reply(&pool, now))
+            .collect()
+    })
+}
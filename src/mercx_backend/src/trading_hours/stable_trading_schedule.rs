@@ -0,0 +1,240 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use crate::pool_analytics::stable_analytics::CAIRO_TIMEZONE_OFFSET_NANOS;
+use crate::stable_schema::envelope::impl_versioned_cbor;
+
+const NANOS_PER_MINUTE: i64 = 60 * 1_000_000_000;
+const NANOS_PER_DAY: i64 = 24 * 60 * NANOS_PER_MINUTE;
+const MINUTES_PER_DAY: u16 = 24 * 60;
+const MAX_UTC_OFFSET_MINUTES: i32 = 14 * 60;
+const MAX_LOOKAHEAD_DAYS: i64 = 400; // long enough to skip a year of holidays
+
+/// Cairo, the timezone the pool analytics already use
+pub const DEFAULT_UTC_OFFSET_MINUTES: i32 = (CAIRO_TIMEZONE_OFFSET_NANOS / NANOS_PER_MINUTE as u64) as i32;
+
+#[derive(CandidType, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Weekday {
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl Weekday {
+    const ALL: [Weekday; 7] = [
+        Weekday::Monday,
+        Weekday::Tuesday,
+        Weekday::Wednesday,
+        Weekday::Thursday,
+        Weekday::Friday,
+        Weekday::Saturday,
+        Weekday::Sunday,
+    ];
+
+    /// Weekday of a day number counted from 1970-01-01, which was a Thursday
+    fn of_day(day: i64) -> Self {
+        Self::ALL[(day + 3).rem_euclid(7) as usize]
+    }
+}
+
+/// Trading window within one local day, minutes since local midnight
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TradingSession {
+    pub weekday: Weekday,
+    pub open_minute: u16,
+    pub close_minute: u16, // exclusive, at most 1440
+}
+
+/// Weekly calendar of a pool. Pools without one trade around the clock
+#[derive(CandidType, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StableTradingSchedule {
+    pub sessions: Vec<TradingSession>,
+    pub utc_offset_minutes: i32, // local time of the sessions and holidays, DEFAULT_UTC_OFFSET_MINUTES for Cairo
+    pub holidays: Vec<String>,   // local dates without trading, YYYY-MM-DD
+}
+
+impl StableTradingSchedule {
+    /// Checks the schedule and sorts sessions and holidays
+    pub fn validate(self) -> Result<Self, String> {
+        if self.utc_offset_minutes.abs() > MAX_UTC_OFFSET_MINUTES {
+            Err(format!("❌ utc_offset_minutes must be within ±{}", MAX_UTC_OFFSET_MINUTES))?
+        }
+        if self.sessions.is_empty() {
+            Err("❌ A schedule needs at least one session, remove it to trade around the clock")?
+        }
+
+        let mut sessions = self.sessions;
+        sessions.sort_by_key(|session| (session.weekday, session.open_minute));
+        for session in &sessions {
+            if session.open_minute >= session.close_minute || session.close_minute > MINUTES_PER_DAY {
+                Err(format!(
+                    "❌ Invalid {:?} session {}-{}, expected open < close <= {}",
+                    session.weekday, session.open_minute, session.close_minute, MINUTES_PER_DAY
+                ))?
+            }
+        }
+        for pair in sessions.windows(2) {
+            if pair[0].weekday == pair[1].weekday && pair[1].open_minute < pair[0].close_minute {
+                Err(format!("❌ Overlapping {:?} sessions", pair[0].weekday))?
+            }
+        }
+
+        let mut holidays = self.holidays;
+        for holiday in &holidays {
+            parse_date(holiday)?;
+        }
+        holidays.sort();
+        holidays.dedup();
+
+        Ok(Self {
+            sessions,
+            holidays,
+            ..self
+        })
+    }
+
+    fn offset_nanos(&self) -> i64 {
+        self.utc_offset_minutes as i64 * NANOS_PER_MINUTE
+    }
+
+    /// (open, close) in UTC nanoseconds of the sessions of a local day
+    fn sessions_on(&self, day: i64) -> impl Iterator<Item = (u64, u64)> + '_ {
+        let day_start = day * NANOS_PER_DAY - self.offset_nanos();
+        let weekday = Weekday::of_day(day);
+        self.sessions
+            .iter()
+            .filter(move |session| session.weekday == weekday)
+            .map(move |session| {
+                let open = day_start + session.open_minute as i64 * NANOS_PER_MINUTE;
+                let close = day_start + session.close_minute as i64 * NANOS_PER_MINUTE;
+                (open.max(0) as u64, close.max(0) as u64)
+            })
+    }
+
+    /// Current and later sessions from ts on, in order. Sessions are sorted by validate
+    fn upcoming_sessions(&self, ts: u64) -> impl Iterator<Item = (u64, u64)> + '_ {
+        let holidays: Vec<i64> = self.holidays.iter().filter_map(|date| parse_date(date).ok()).collect();
+        let today = (ts as i64 + self.offset_nanos()).div_euclid(NANOS_PER_DAY);
+        (today..=today + MAX_LOOKAHEAD_DAYS)
+            .filter(move |day| !holidays.contains(day))
+            .flat_map(move |day| self.sessions_on(day))
+            .filter(move |&(_, close)| close > ts)
+    }
+
+    pub fn is_open(&self, ts: u64) -> bool {
+        self.upcoming_sessions(ts).next().is_some_and(|(open, _)| open <= ts)
+    }
+
+    /// Start of the next session after ts
+    pub fn next_open(&self, ts: u64) -> Option<u64> {
+        self.upcoming_sessions(ts).map(|(open, _)| open).find(|&open| open > ts)
+    }
+
+    /// End of the current session, or of the next one when closed
+    pub fn next_close(&self, ts: u64) -> Option<u64> {
+        self.upcoming_sessions(ts).next().map(|(_, close)| close)
+    }
+}
+
+/// Day number counted from 1970-01-01 of a YYYY-MM-DD date
+pub fn parse_date(date: &str) -> Result<i64, String> {
+    let invalid = || format!("❌ Invalid date '{}', expected YYYY-MM-DD", date);
+    let parts: Vec<&str> = date.split('-').collect();
+    let [year, month, day] = parts.as_slice() else {
+        return Err(invalid());
+    };
+    if year.len() != 4 || month.len() != 2 || day.len() != 2 {
+        return Err(invalid());
+    }
+    let year: i64 = year.parse().map_err(|_| invalid())?;
+    let month: i64 = month.parse().map_err(|_| invalid())?;
+    let day: i64 = day.parse().map_err(|_| invalid())?;
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    let days_in_month = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return Err(invalid()),
+    };
+    if day < 1 || day > days_in_month {
+        return Err(invalid());
+    }
+
+    // days from civil, counted from 0000-03-01 so the leap day ends the year
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    Ok(era * 146_097 + day_of_era - 719_468)
+}
+
+impl_versioned_cbor!(StableTradingSchedule);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: u64 = NANOS_PER_MINUTE as u64;
+
+    // 10:00-14:30 Cairo time, Sunday to Thursday
+    fn egx() -> StableTradingSchedule {
+        let sessions = [Weekday::Sunday, Weekday::Monday, Weekday::Tuesday, Weekday::Wednesday, Weekday::Thursday]
+            .into_iter()
+            .map(|weekday| TradingSession {
+                weekday,
+                open_minute: 600,
+                close_minute: 870,
+            })
+            .collect();
+        StableTradingSchedule {
+            sessions,
+            utc_offset_minutes: DEFAULT_UTC_OFFSET_MINUTES,
+            holidays: vec!["2026-10-06".to_string()],
+        }
+        .validate()
+        .unwrap()
+    }
+
+    fn utc(date: &str, minute: u64) -> u64 {
+        parse_date(date).unwrap() as u64 * NANOS_PER_DAY as u64 + minute * MINUTE
+    }
+
+    #[test]
+    fn parse_date_counts_from_epoch() {
+        assert_eq!(parse_date("1970-01-01"), Ok(0));
+        assert_eq!(parse_date("2000-03-01"), Ok(11_017));
+        assert!(parse_date("2026-02-29").is_err());
+        assert!(parse_date("2026-1-05").is_err());
+    }
+
+    #[test]
+    fn sessions_follow_the_local_calendar() {
+        let schedule = egx();
+        // Thursday 2026-10-15, 07:00 UTC is 10:00 in Cairo
+        assert!(!schedule.is_open(utc("2026-10-15", 6 * 60 + 59)));
+        assert!(schedule.is_open(utc("2026-10-15", 7 * 60)));
+        assert_eq!(schedule.next_close(utc("2026-10-15", 8 * 60)), Some(utc("2026-10-15", 11 * 60 + 30)));
+        // closed over the weekend, reopens Sunday
+        assert!(!schedule.is_open(utc("2026-10-16", 8 * 60)));
+        assert_eq!(schedule.next_open(utc("2026-10-15", 8 * 60)), Some(utc("2026-10-18", 7 * 60)));
+        // Tuesday 2026-10-06 is a holiday
+        assert!(!schedule.is_open(utc("2026-10-06", 8 * 60)));
+        assert_eq!(schedule.next_open(utc("2026-10-06", 8 * 60)), Some(utc("2026-10-07", 7 * 60)));
+    }
+
+    #[test]
+    fn validate_rejects_overlapping_sessions() {
+        let mut schedule = egx();
+        schedule.sessions.push(TradingSession {
+            weekday: Weekday::Monday,
+            open_minute: 800,
+            close_minute: 900,
+        });
+        assert!(schedule.validate().is_err());
+    }
+}
@@ -0,0 +1,14 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+use super::stable_trading_schedule::StableTradingSchedule;
+
+#[derive(CandidType, Debug, Clone, Serialize, Deserialize)]
+pub struct TradingHoursReply {
+    pub pool_id: u32,
+    pub symbol: String,
+    pub schedule: Option<StableTradingSchedule>, // None trades around the clock
+    pub is_open: bool,
+    pub next_open: Option<u64>,  // UTC nanoseconds, None when open around the clock
+    pub next_close: Option<u64>, // end of the current session, or of the next one when closed
+}
@@ -1,23 +1,114 @@
-use candid::Nat;
+use candid::{Nat, Principal};
 
 use super::tx_id::TxId;
-use crate::stable_memory::TRANSFERS;
+use crate::stable_memory::{POOLS, TRANSFERS, TRANSFER_PRINCIPAL_INDEX, TRANSFER_TS_INDEX, TRANSFER_TX_INDEX};
 use crate::transfers::stable_transfer::{StableTransfer, StableTransferId};
+use crate::transfers::stable_transfer_index::{StableTransferPrincipalKey, StableTransferTsKey, StableTransferTxKey};
 use crate::stable_mercx_settings::mercx_settings_map;
 use crate::stable_mercx_settings::mercx_settings_map::reset_transfers_map_idx;
+use crate::stable_memory::TRANSFER_ARCHIVE;
+use crate::archive::handlers::get_archived_transfer;
+use crate::pool_analytics::analytics::get_token_price_usd;
+use crate::token::handlers::get_by_token_id;
+use crate::rbac::guards::is_admin;
+use crate::block_log::dex_block::log_admin_action;
 
+/// Looks in TRANSFERS first and then in the archive
 pub fn get_by_transfer_id(transfer_id: u64) -> Option<StableTransfer> {
-    TRANSFERS.with(|m| m.borrow().get(&StableTransferId(transfer_id)))
-  
+    TRANSFERS
+        .with(|m| m.borrow().get(&StableTransferId(transfer_id)))
+        .or_else(|| get_archived_transfer(transfer_id))
 }
 
 
 pub fn exist(token_id: u32, block_id: &Nat) -> bool {
-    TRANSFERS.with(|m| {
+    let key = StableTransferTxKey(token_id, TxId::BlockIndex(block_id.clone()));
+    TRANSFER_TX_INDEX.with(|m| m.borrow().contains_key(&key))
+}
+
+/// Transfer recorded for a ledger block of a token
+pub fn get_by_block_id(token_id: u32, block_id: &Nat) -> Option<StableTransfer> {
+    let key = StableTransferTxKey(token_id, TxId::BlockIndex(block_id.clone()));
+    TRANSFER_TX_INDEX
+        .with(|m| m.borrow().get(&key))
+        .and_then(get_by_transfer_id)
+}
+
+/// Drops a transfer recorded ahead of a ledger call that surely failed, with its index entries
+pub fn remove(transfer_id: u64) {
+    let Some(transfer) = TRANSFERS.with(|m| m.borrow_mut().remove(&StableTransferId(transfer_id))) else {
+        return;
+    };
+    TRANSFER_TX_INDEX.with(|m| m.borrow_mut().remove(&StableTransferTxKey(transfer.token_id, transfer.tx_id)));
+    TRANSFER_TS_INDEX.with(|m| m.borrow_mut().remove(&StableTransferTsKey(transfer.ts, transfer_id)));
+    if let Some(account) = transfer.account {
+        TRANSFER_PRINCIPAL_INDEX.with(|m| m.borrow_mut().remove(&StableTransferPrincipalKey(account.owner, transfer_id)));
+    }
+}
+
+/// Transfers with from_ts <= ts < to_ts, oldest first
+pub fn get_by_time_range(from_ts: u64, to_ts: u64) -> Vec<StableTransfer> {
+    if from_ts >= to_ts {
+        return Vec::new();
+    }
+    let transfer_ids: Vec<u64> = TRANSFER_TS_INDEX.with(|m| {
         m.borrow()
-            .iter()
-            .any(|(_, v)| v.token_id == token_id && v.tx_id == TxId::BlockIndex(block_id.clone()))
-    })
+            .range(StableTransferTsKey(from_ts, 0)..StableTransferTsKey(to_ts, 0))
+            .map(|(key, _)| key.1)
+            .collect()
+    });
+    transfer_ids.into_iter().filter_map(get_by_transfer_id).collect()
+}
+
+/// Transfers to or from an account of `principal`, oldest first
+pub fn get_by_principal(principal: Principal) -> Vec<StableTransfer> {
+    let transfer_ids: Vec<u64> = TRANSFER_PRINCIPAL_INDEX.with(|m| {
+        m.borrow()
+            .range(StableTransferPrincipalKey(principal, 0)..=StableTransferPrincipalKey(principal, u64::MAX))
+            .map(|(key, _)| key.1)
+            .collect()
+    });
+    transfer_ids.into_iter().filter_map(get_by_transfer_id).collect()
+}
+
+fn index(transfer: &StableTransfer) {
+    TRANSFER_TX_INDEX.with(|m| {
+        m.borrow_mut()
+            .insert(StableTransferTxKey(transfer.token_id, transfer.tx_id.clone()), transfer.transfer_id)
+    });
+    TRANSFER_TS_INDEX.with(|m| m.borrow_mut().insert(StableTransferTsKey(transfer.ts, transfer.transfer_id), ()));
+    if let Some(account) = transfer.account {
+        TRANSFER_PRINCIPAL_INDEX
+            .with(|m| m.borrow_mut().insert(StableTransferPrincipalKey(account.owner, transfer.transfer_id), ()));
+    }
+}
+
+/// Backfill the transfer indexes for transfers stored before they existed.
+/// Skipped when the indexes already cover TRANSFERS. Legacy transfers without an account
+/// stay out of the principal index.
+pub fn rebuild_transfer_indexes() {
+    let transfer_count = TRANSFERS.with(|m| m.borrow().len()) + TRANSFER_ARCHIVE.with(|m| m.borrow().len());
+    let indexed_count = TRANSFER_TS_INDEX.with(|m| m.borrow().len());
+    if transfer_count == indexed_count {
+        return;
+    }
+    reindex_transfers();
+}
+
+/// Rebuild the tx_id, timestamp and principal indexes from TRANSFERS and TRANSFER_ARCHIVE
+pub fn reindex_transfers() {
+    let transfer_count = TRANSFERS.with(|m| m.borrow().len()) + TRANSFER_ARCHIVE.with(|m| m.borrow().len());
+    TRANSFER_TX_INDEX.with(|m| m.borrow_mut().clear_new());
+    TRANSFER_TS_INDEX.with(|m| m.borrow_mut().clear_new());
+    TRANSFER_PRINCIPAL_INDEX.with(|m| m.borrow_mut().clear_new());
+    for transfers in [&TRANSFERS, &TRANSFER_ARCHIVE] {
+        transfers.with(|m| {
+            for (_, transfer) in m.borrow().iter() {
+                index(&transfer);
+            }
+        });
+    }
+    ic_cdk::println!("Transfer indexes rebuilt for {} transfers", transfer_count);
 }
 
 pub fn insert(transfer: &StableTransfer) -> u64 {
@@ -27,17 +118,104 @@ pub fn insert(transfer: &StableTransfer) -> u64 {
     let transfer_id = mercx_settings_map::inc_transfer_map_idx();
 
     ic_cdk::println!("🔍 TRANSFERS accessed here");
+    let insert_transfer = StableTransfer {
+        transfer_id,
+        ..transfer.clone()
+    };
+    TRANSFERS.with(|m| {
+        m.borrow_mut().insert(StableTransferId(transfer_id), insert_transfer.clone());
+    });
+    index(&insert_transfer);
+    ic_cdk::spawn(update_usd_price(transfer_id, transfer.token_id));
+    transfer_id
+}
+
+/// Store the token's USD price on a new transfer for accounting, left None if the price lookup fails
+async fn update_usd_price(transfer_id: u64, token_id: u32) {
+    let Some(token) = get_by_token_id(token_id) else {
+        return;
+    };
+    let usd_price = match get_token_price_usd(&token).await {
+        Ok(usd_price) if usd_price > 0.0 => usd_price,
+        // 0.0 means no pool or rate source knows the token
+        Ok(_) => return,
+        Err(e) => {
+            ic_cdk::println!("❌ Transfer #{} USD price not set: {}", transfer_id, e);
+            return;
+        }
+    };
+
     TRANSFERS.with(|m| {
         let mut map = m.borrow_mut();
-        let insert_transfer = StableTransfer {
-            transfer_id,
-            ..transfer.clone()
-        };
-        map.insert(StableTransferId(transfer_id), insert_transfer);
-        transfer_id
-    })
+        if let Some(transfer) = map.get(&StableTransferId(transfer_id)) {
+            map.insert(StableTransferId(transfer_id), StableTransfer { usd_price: Some(usd_price), ..transfer });
+        }
+    });
+}
+
+
+/// Tag the transfers of one request with a new shared request_id and the pools it went through.
+/// Called once per swap/liquidity/balance call, after its last transfer. Returns the request_id.
+pub fn link(transfer_ids: &[u64], pool_ids: &[u32]) -> u64 {
+    let request_id = mercx_settings_map::inc_request_map_idx();
+    TRANSFERS.with(|m| {
+        let mut map = m.borrow_mut();
+        for transfer_id in transfer_ids {
+            if let Some(transfer) = map.get(&StableTransferId(*transfer_id)) {
+                let linked_transfer = StableTransfer {
+                    pool_ids: pool_ids.to_vec(),
+                    request_id: Some(request_id),
+                    ..transfer
+                };
+                map.insert(StableTransferId(*transfer_id), linked_transfer);
+            }
+        }
+    });
+    request_id
 }
 
+/// Link transfers recorded before request_id and pool_ids existed.
+/// Those were only related by a shared timestamp, so each timestamp becomes one request and gets
+/// the pools whose both tokens appear in it. Transfers already linked are skipped, so this only
+/// does work on the first upgrade after the schema change.
+pub fn migrate_transfer_links() {
+    let unlinked: Vec<StableTransfer> = TRANSFER_TS_INDEX.with(|index| {
+        TRANSFERS.with(|m| {
+            let map = m.borrow();
+            index
+                .borrow()
+                .iter()
+                .filter_map(|(key, _)| map.get(&StableTransferId(key.1)))
+                .filter(|transfer| transfer.request_id.is_none())
+                .collect()
+        })
+    });
+    if unlinked.is_empty() {
+        return;
+    }
+
+    let pools: Vec<(u32, u32, u32)> = POOLS.with(|m| {
+        m.borrow()
+            .iter()
+            .map(|(_, pool)| (pool.pool_id, pool.token_id_0, pool.token_id_1))
+            .collect()
+    });
+
+    // unlinked is ordered by ts, so transfers of one legacy request are next to each other
+    let mut request_count = 0;
+    for group in unlinked.chunk_by(|a, b| a.ts == b.ts) {
+        let token_ids: Vec<u32> = group.iter().map(|transfer| transfer.token_id).collect();
+        let pool_ids: Vec<u32> = pools
+            .iter()
+            .filter(|(_, token_id_0, token_id_1)| token_ids.contains(token_id_0) && token_ids.contains(token_id_1))
+            .map(|(pool_id, _, _)| *pool_id)
+            .collect();
+        let transfer_ids: Vec<u64> = group.iter().map(|transfer| transfer.transfer_id).collect();
+        link(&transfer_ids, &pool_ids);
+        request_count += 1;
+    }
+    ic_cdk::println!("Linked {} legacy transfers into {} requests", unlinked.len(), request_count);
+}
 
 pub fn get_by_token_ids(token_id_0: u32, token_id_1: u32) -> Vec<u64> {
     TRANSFERS.with(|m| {
@@ -55,13 +233,18 @@ pub fn get_by_token_ids(token_id_0: u32, token_id_1: u32) -> Vec<u64> {
 }
 
 #[cfg(not(feature = "prod"))]
-#[ic_cdk::update]
+#[ic_cdk::update(guard = "is_admin")]
 fn reset_transfers() -> Result<String, String> {
     TRANSFERS.with(|transfers| {
         transfers.borrow_mut().clear_new(); // `clear_new()` btmsh kolo remove law hanmsh haga specific
     });
+    TRANSFER_ARCHIVE.with(|m| m.borrow_mut().clear_new());
+    TRANSFER_TX_INDEX.with(|m| m.borrow_mut().clear_new());
+    TRANSFER_TS_INDEX.with(|m| m.borrow_mut().clear_new());
+    TRANSFER_PRINCIPAL_INDEX.with(|m| m.borrow_mut().clear_new());
 
     reset_transfers_map_idx();
+    log_admin_action("reset_transfers", "");
 
     Ok("✅ Tokens memory cleared".to_string())
 }
@@ -1,5 +1,7 @@
 pub mod stable_transfer;
+pub mod stable_transfer_index;
 pub mod tx_id;
 pub mod handlers;
 pub mod transfer_reply;
-pub mod transfer_reply_helpers;
\ No newline at end of file
+pub mod transfer_reply_helpers;
+pub mod recover_deposit;
\ No newline at end of file
@@ -0,0 +1,105 @@
+use candid::Nat;
+use icrc_ledger_types::icrc1::account::Account;
+use icrc_ledger_types::icrc1::transfer::TransferError;
+
+use crate::helpers::math_helpers::nat_subtract;
+use crate::ic::general::get_time;
+use crate::ic::id::caller_id;
+use crate::ic::transfer::{icrc1_transfer_checked, TransferFailure};
+use crate::ic::verify_transfer::verify_transfer_since;
+use crate::token::handlers::get_by_token;
+use crate::transfers::handlers::{exist, get_by_block_id, get_by_transfer_id, insert, link, remove};
+use crate::transfers::stable_transfer::{StableTransfer, TransferType};
+use crate::transfers::tx_id::TxId;
+
+/// Recovery recorded for the caller whose refund has not gone through yet
+fn is_unsettled_recovery(transfer: &StableTransfer, caller_id: &Account) -> bool {
+    transfer.transfer_type == TransferType::Recovery
+        && transfer.is_send
+        && transfer.request_id.is_none()
+        && transfer.account.as_ref() == Some(caller_id)
+}
+
+/// Refund a deposit the caller sent with icrc1_transfer that no swap or liquidity call consumed.
+/// The block must be a transfer of `amount` from the caller to the backend that is not in TRANSFERS yet.
+/// The deposit is recorded as consumed before the refund so it can only be recovered once. The record is
+/// dropped again when the ledger refuses the refund, and a refund with an unknown outcome is retried by
+/// calling again, with the same created_at_time so the ledger pays it once.
+/// Returns the refund block index, the refund is amount minus the ledger fee.
+#[ic_cdk::update]
+pub async fn recover_deposit(token: String, block_index: Nat, amount: Nat) -> Result<Nat, String> {
+    let token = get_by_token(&token)?;
+    let token_id = token.token_id();
+    let refund_amount = match nat_subtract(&amount, &token.fee()) {
+        Some(refund_amount) if refund_amount > 0_u32 => refund_amount,
+        _ => Err(format!("Deposit amount must be above the ledger fee {}", token.fee()))?,
+    };
+    let caller_id = caller_id();
+
+    let deposit = match get_by_block_id(token_id, &block_index) {
+        Some(deposit) if is_unsettled_recovery(&deposit, &caller_id) && deposit.amount == amount => deposit,
+        Some(_) => Err(format!("Block id #{} was already used", block_index))?,
+        None => {
+            // orphaned deposits are usually past the transfer expiry, so no expiry check
+            verify_transfer_since(&token, &block_index, &amount, 0).await?;
+
+            // check again after the await, a concurrent swap or recovery may have consumed it
+            if exist(token_id, &block_index) {
+                Err(format!("Block id #{} was already used", block_index))?
+            }
+
+            let deposit_transfer_id = insert(&StableTransfer {
+                transfer_id: 0,
+                is_send: true,
+                amount: amount.clone(),
+                token_id,
+                tx_id: TxId::BlockIndex(block_index.clone()),
+                transfer_type: TransferType::Recovery,
+                ts: get_time(),
+                account: Some(caller_id),
+                pool_ids: Vec::new(),
+                request_id: None,
+                usd_price: None,
+            });
+            get_by_transfer_id(deposit_transfer_id).ok_or("❌ Recovery record missing")?
+        }
+    };
+
+    let refund_block_id = match icrc1_transfer_checked(&refund_amount, None, &caller_id, &token, Some(deposit.ts)).await {
+        Ok(block_id) | Err(TransferFailure::Ledger(TransferError::Duplicate { duplicate_of: block_id })) => block_id,
+        // past the ledger's deduplication window a retry could pay twice
+        Err(TransferFailure::Ledger(TransferError::TooOld)) => Err(format!(
+            "❌ Refund of deposit #{} is too old to retry, an admin has to check the ledger",
+            block_index
+        ))?,
+        Err(TransferFailure::Ledger(e)) => {
+            remove(deposit.transfer_id);
+            Err(format!("Refund of deposit #{} failed, it can be recovered again: {}", block_index, e))?
+        }
+        Err(TransferFailure::Call(e)) => Err(format!(
+            "Refund of deposit #{} has an unknown outcome, call recover_deposit again to retry: {}",
+            block_index, e
+        ))?,
+    };
+
+    // a concurrent retry may have settled it while the refund was awaited
+    if get_by_transfer_id(deposit.transfer_id).is_some_and(|deposit| deposit.request_id.is_none()) {
+        let refund_transfer_id = insert(&StableTransfer {
+            transfer_id: 0,
+            is_send: false,
+            amount: refund_amount,
+            token_id,
+            tx_id: TxId::BlockIndex(refund_block_id.clone()),
+            transfer_type: TransferType::Recovery,
+            ts: get_time(),
+            account: Some(caller_id),
+            pool_ids: Vec::new(),
+            request_id: None,
+            usd_price: None,
+        });
+        link(&[deposit.transfer_id, refund_transfer_id], &[]);
+        ic_cdk::println!("💬 Recovered {} deposit #{} for {}", token.symbol(), block_index, caller_id);
+    }
+
+    Ok(refund_block_id)
+}
@@ -1,9 +1,11 @@
 use candid::{CandidType, Nat};
+use icrc_ledger_types::icrc1::account::Account;
 use ic_stable_structures::{storable::Bound, Storable};
 use serde::{Deserialize, Serialize};
 use ic_cdk::trap;
 
 use super::tx_id::TxId;
+use crate::stable_schema::envelope::impl_versioned_cbor;
 
 
 #[derive(CandidType, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
@@ -39,6 +41,14 @@ pub struct StableTransfer {
     pub tx_id: TxId,   //The ID of the actual blockchain-level transaction.
     pub transfer_type: TransferType, // NEW FIELD: Type of transfer
     pub ts: u64,
+    #[serde(default)]
+    pub account: Option<Account>, // user side of the transfer, None for legacy transfers and ICP account id payouts
+    #[serde(default)]
+    pub pool_ids: Vec<u32>, // pools the request went through, empty for deposits, withdrawals and failed swaps
+    #[serde(default)]
+    pub request_id: Option<u64>, // shared by all transfers of one swap/liquidity/balance call, set by handlers::link
+    #[serde(default)]
+    pub usd_price: Option<f64>, // USD price of one token at the time of the transfer, set shortly after it is recorded
 }
 
 // Enum to identify different types of transfers
@@ -49,23 +59,9 @@ pub enum TransferType {
     LiquidityRemove,// For removing liquidity
     Transfer,            // For fee collection
     Other,          // For other types of transfers
+    Deposit,        // Deposit subaccount swept into the internal balance book
+    Withdraw,       // Internal balance withdrawn to the user's wallet
+    Recovery,       // Orphaned deposit consumed and refunded by recover_deposit
 }
 
-impl Storable for StableTransfer {
-    fn to_bytes(&self) -> std::borrow::Cow<'_, [u8]> {
-        serde_cbor::to_vec(self)
-            .unwrap_or_else(|e| {
-                trap(&format!("❌ Failed to serialize StableTransfer: {:?}", e));
-            })
-            .into()
-    }
-
-    fn from_bytes(bytes: std::borrow::Cow<'_, [u8]>) -> Self {
-        serde_cbor::from_slice(&bytes)
-            .unwrap_or_else(|e| {
-                trap(&format!("❌ Failed to deserialize StableTransfer: {:?}", e));
-            })
-    }
-
-    const BOUND: Bound = Bound::Unbounded;
-}
+impl_versioned_cbor!(StableTransfer);
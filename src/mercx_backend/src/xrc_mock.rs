@@ -1,7 +1,7 @@
-use candid::{CandidType, Deserialize, Principal};
+use candid::{CandidType, Deserialize};
 //use crate::get_icp_rate_in_cents;
 use ic_cdk::update;
-use crate::ic::canister_address::CANISTER_ID_XRC;
+use crate::ic::canister_ids;
 
 #[derive(CandidType, Deserialize, Debug)]
 pub struct Metadata {
@@ -83,7 +83,7 @@ pub async fn get_icp_rate() -> Result<f64, String> {
         timestamp: None,
     };
 
-    let xrc_canister_id = Principal::from_text(CANISTER_ID_XRC).unwrap();
+    let xrc_canister_id = canister_ids::xrc_canister();
 
     let call_result: Result<Vec<u8>, (ic_cdk::api::call::RejectionCode, String)> =
         ic_cdk::api::call::call_raw(
@@ -175,8 +175,7 @@ async fn fetch_rate_generic(base_asset: Asset) -> Result<f64, String> {
         timestamp: None,
     };
 
-    let xrc_canister_id = Principal::from_text(CANISTER_ID_XRC)
-        .map_err(|e| format!("Bad XRC canister id: {e}"))?;
+    let xrc_canister_id = canister_ids::xrc_canister();
 
 
 